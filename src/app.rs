@@ -1,11 +1,24 @@
 use crossterm::event::{self, Event, KeyCode};
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
-use crate::file_watcher::file_watcher::FileWatcher;
-use crate::markdown_serialiser::*;
+use similar::TextDiff;
 
-use crate::{info_subtype::InfoSubType, log_type::LogType, node::Node, traits::EventHandler};
+use crate::backups::{self, BackupEntry, SAVE_BACKUP_NAME};
+use crate::file_watcher::file_watcher::FileWatcher;
+use crate::format_type::FormatType;
+use crate::archive;
+use crate::project_settings::{ArchiveMode, ProjectSettings, WritebackMode};
+use crate::reports::{ExportKind, html, ical, timesheet, timewarrior};
+
+use crate::{
+    diagnostic::Diagnostic,
+    info_subtype::InfoSubType,
+    log_type::LogType,
+    node::{ARCHIVE_HEADING, Node},
+    traits::{EventHandler, TaskFormat},
+};
 
 use crate::components::Window;
 
@@ -26,6 +39,16 @@ pub struct App {
     last_save_tick: Instant,
 
     session_state: SessionState,
+    format: Box<dyn TaskFormat>,
+    settings: ProjectSettings,
+    diagnostics: Vec<Diagnostic>,
+
+    writeback_override: Option<WritebackMode>,
+    pending_writeback: Option<Vec<(PathBuf, String)>>,
+    rejected_writeback: Option<Vec<(PathBuf, String)>>,
+    quit_after_writeback: bool,
+    /// Task files this app has written whose change events the watcher has not reported yet
+    written_files: Vec<(PathBuf, String)>,
 }
 
 impl App {
@@ -39,9 +62,32 @@ impl App {
             last_save_tick: Instant::now(),
 
             session_state: SessionState::default(),
+            format: FormatType::Markdown.format(false),
+            settings: ProjectSettings::default(),
+            diagnostics: Vec::new(),
+
+            writeback_override: None,
+            pending_writeback: None,
+            rejected_writeback: None,
+            quit_after_writeback: false,
+            written_files: Vec::new(),
         }
     }
 
+    /// Uses `mode` regardless of the writeback setting in the task file.
+    pub fn set_writeback_override(&mut self, mode: WritebackMode) {
+        self.writeback_override = Some(mode);
+    }
+
+    fn writeback_mode(&self) -> WritebackMode {
+        self.writeback_override.unwrap_or(self.settings.writeback)
+    }
+
+    pub fn set_format(&mut self, format: Box<dyn TaskFormat>) {
+        self.window.set_fixed_layout(format.fixed_layout());
+        self.format = format;
+    }
+
     pub fn load(&mut self) {
         assert!(self.file_watcher.is_some());
 
@@ -69,16 +115,44 @@ impl App {
 
         let save_file = format!("{save_dir}/save.json");
 
-        if let Ok(save_contents) = fs::read_to_string(save_file) {
-            let deserialised: Window = serde_json::from_str(&save_contents).unwrap();
-            self.window.load(deserialised);
-            self.window
-                .log("Retrieved save file.", LogType::INFO(InfoSubType::General));
-        } else {
-            self.window.log("Could not retrieve save file", LogType::ERROR);
+        // A project opened for the first time has nothing saved yet
+        let Ok(save_contents) = fs::read_to_string(save_file) else {
+            return;
+        };
+
+        match serde_json::from_str::<Window>(&save_contents) {
+            Ok(deserialised) => {
+                self.window.load(deserialised);
+                self.root_node = self.window.extract_node();
+                self.window
+                    .log("Retrieved save file.", LogType::INFO(InfoSubType::General));
+            }
+            Err(e) => self
+                .window
+                .log(&format!("Could not retrieve save file: {}", e), LogType::ERROR),
         }
     }
 
+    /// Where the saved state for the task file at `file_path` is kept.
+    pub fn save_dir_for(file_path: &Path) -> String {
+        let home_dir = std::env::home_dir().unwrap().to_string_lossy().to_string();
+        let dir_name = file_path
+            .parent()
+            .and_then(|p| p.file_name())
+            .and_then(|str| str.to_str())
+            .unwrap_or_default();
+
+        format!("{}/.project-saves/{}", home_dir, dir_name)
+    }
+
+    fn save_dir(&self) -> String {
+        App::save_dir_for(&self.file_watcher.as_ref().unwrap().file_path)
+    }
+
+    fn backup_dir(&self) -> PathBuf {
+        PathBuf::from(format!("{}/backups", self.save_dir()))
+    }
+
     fn project_dir_name(&self) -> String {
         let path = self.file_watcher.as_ref().unwrap();
         let dir_name = path
@@ -94,15 +168,94 @@ impl App {
     pub fn attach_file_watcher(&mut self, file_name: &str) -> Result<(), notify::Error> {
         let watcher = FileWatcher::new(file_name)?;
         self.file_watcher = Some(watcher);
+        self.window.set_backup_dir(self.backup_dir());
 
         let initial_contents = self.file_watcher.as_ref().unwrap().read_file();
-        let markdown_tree = Node::convert_from(&initial_contents);
-        self.root_node = markdown_tree.clone();
-        self.window.update_tree(markdown_tree);
+        let content_tree = self.parse_tree(&initial_contents)?;
+        self.root_node = content_tree.clone();
+        self.window.update_tree(content_tree);
 
         Ok(())
     }
 
+    /// Attaches the time of Timewarrior intervals to matching tasks. Intervals that match no
+    /// task are kept in the window to be reviewed and added to a task by hand.
+    pub fn import_timewarrior(&mut self, data_path: &Path) -> Result<(), String> {
+        let intervals = timewarrior::read_intervals(data_path)?;
+        let (imported, unmatched) = self.window.import_intervals(intervals)?;
+
+        let mut message = format!("Imported {} Timewarrior intervals", imported);
+        if unmatched > 0 {
+            message.push_str(&format!(", {} match no task and are waiting for review", unmatched));
+        }
+        self.window.log(&message, LogType::INFO(InfoSubType::General));
+
+        Ok(())
+    }
+
+    fn parse_tree(&mut self, buf: &str) -> Result<Node, notify::Error> {
+        let file_watcher = self.file_watcher.as_mut().unwrap();
+        let (mut tree, diagnostics) = self.format.parse(buf, &file_watcher.file_path);
+        file_watcher.watch_includes(tree.included_files())?;
+
+        // Only report problems that were not already present on the previous parse
+        for diagnostic in diagnostics.iter() {
+            if !self.diagnostics.contains(diagnostic) {
+                self.window.log_diagnostic(diagnostic);
+            }
+        }
+        self.diagnostics = diagnostics;
+
+        match ProjectSettings::from_front_matter(tree.front_matter.as_deref()) {
+            Ok(settings) => self.settings = settings,
+            Err(e) => self.window.log(&e, LogType::ERROR),
+        }
+
+        tree.mark_excluded(&self.settings.exclude_headings);
+
+        Ok(tree)
+    }
+
+    fn export(&self, kind: ExportKind) -> Result<PathBuf, String> {
+        let file_path = &self.file_watcher.as_ref().unwrap().file_path;
+        let state = self.window.saved_state();
+
+        let (output_path, contents) = match kind {
+            ExportKind::Html => (html::default_output_path(file_path), html::render(&state, &self.project_name())),
+            ExportKind::Calendar => (
+                ical::default_output_path(file_path),
+                ical::render(&state.sessions, &self.project_name()),
+            ),
+        };
+
+        match fs::write(&output_path, contents) {
+            Ok(()) => Ok(output_path),
+            Err(e) => Err(format!("Could not write {}: {}", output_path.display(), e)),
+        }
+    }
+
+    /// Moves completed tasks to where the `archive` setting says, and returns where that is.
+    fn archive(&mut self) -> Result<String, String> {
+        if self.settings.archive == ArchiveMode::Section {
+            self.window.archive_completed(true, |_| Ok(()))?;
+            return Ok(format!("{} heading", ARCHIVE_HEADING));
+        }
+
+        // The tasks only leave the tree once they are safely in the archive file
+        let archive_path = archive::archive_file_path(&self.file_watcher.as_ref().unwrap().file_path);
+        self.window
+            .archive_completed(false, |archived| archive::append_to_file(&archive_path, archived))?;
+
+        Ok(archive_path.display().to_string())
+    }
+
+    fn project_name(&self) -> String {
+        match &self.settings.project_name {
+            Some(name) => name.clone(),
+            None => self.project_dir_name(),
+        }
+    }
+
     pub fn run(&mut self) {
         if let None = self.file_watcher {
             println!("File watcher has not been set.");
@@ -111,23 +264,89 @@ impl App {
 
         let mut terminal = ratatui::init();
 
-        self.window.title = self.project_dir_name();
+        self.window.title = self.project_name();
         self.window
             .log("Launched project", LogType::INFO(InfoSubType::General));
 
         loop {
+            if let Some(entry) = self.window.take_restore() {
+                match self.restore_backup(&entry) {
+                    Ok(()) => self.window.log(
+                        &format!("Restored {} from backup", entry.original_name),
+                        LogType::INFO(InfoSubType::General),
+                    ),
+                    Err(e) => self.window.log(&e, LogType::ERROR),
+                }
+            }
+
+            if let Some(kind) = self.window.take_export_request() {
+                match self.export(kind) {
+                    Ok(path) => self.window.log(
+                        &format!("Exported {} to {}", kind, path.display()),
+                        LogType::INFO(InfoSubType::General),
+                    ),
+                    Err(e) => self.window.log(&e, LogType::ERROR),
+                }
+            }
+
+            if let Some(data_path) = self.window.take_import_request()
+                && let Err(e) = self.import_timewarrior(&data_path)
+            {
+                self.window.log(&e, LogType::ERROR);
+            }
+
+            if self.window.take_archive_request() {
+                match self.archive() {
+                    Ok(location) => self.window.log(
+                        &InfoSubType::ArchiveTasks.message(location),
+                        LogType::INFO(InfoSubType::ArchiveTasks),
+                    ),
+                    Err(e) => self.window.log(&e, LogType::ERROR),
+                }
+            }
+
+            if let Some(accepted) = self.window.take_writeback_decision() {
+                if let Err(e) = self.resolve_writeback(accepted) {
+                    self.window.log(&e, LogType::ERROR);
+                }
+
+                if self.quit_after_writeback {
+                    break;
+                }
+            }
+
             if self.session_state == SessionState::Quitting {
+                self.window.close_session();
                 if let Err(e) = self.save() {
                     // TODO: print save success after terminal has quit
                     println!("{e}");
                 }
-                break;
+
+                // Stay open until the pending changes have been accepted or rejected
+                if self.pending_writeback.is_some() {
+                    self.quit_after_writeback = true;
+                    self.session_state = SessionState::AwaitingPrompt;
+                } else {
+                    break;
+                }
             }
 
-            if let Some(buf) = self.file_watcher.as_mut().unwrap().poll_change() {
-                let new_content_tree = Node::convert_from(&buf);
-                self.root_node = new_content_tree.clone();
-                self.window.update_tree(new_content_tree);
+            // Changes this app wrote itself are already in the tree. Outside changes wait in the
+            // watcher until any open modal is closed
+            let change = if self.window.has_open_modal() {
+                None
+            } else {
+                self.file_watcher.as_mut().unwrap().poll_change()
+            };
+            if let Some(buf) = change.and_then(|(paths, buf)| self.has_outside_change(&paths).then_some(buf)) {
+                match self.parse_tree(&buf) {
+                    Ok(new_content_tree) => {
+                        self.root_node = new_content_tree.clone();
+                        self.window.reload_tree(new_content_tree);
+                        self.window.title = self.project_name();
+                    }
+                    Err(e) => self.window.log(&e.to_string(), LogType::ERROR),
+                }
             }
 
             if self.last_update_tick.elapsed().as_secs() >= 1 {
@@ -135,7 +354,7 @@ impl App {
                 self.last_update_tick = Instant::now();
             }
 
-            if self.last_save_tick.elapsed().as_secs() >= 60 {
+            if self.last_save_tick.elapsed().as_secs() >= self.settings.save_interval {
                 match self.save() {
                     Ok(()) => self.window.log(
                         &InfoSubType::Save.message(InfoSubType::Save),
@@ -199,15 +418,228 @@ impl App {
         }
 
         let save_file = format!("{save_dir}/save.json");
-
         let serialised = serde_json::to_string_pretty(&self.window).unwrap();
+
+        // Every save logs itself, so saves that changed nothing else are not worth a backup
+        let previous = fs::read_to_string(&save_file).unwrap_or_default();
+        if App::without_logs(&previous) != App::without_logs(&serialised) {
+            let retention = self.settings.backup_retention;
+            backups::backup_file(&self.backup_dir(), Path::new(&save_file), SAVE_BACKUP_NAME, retention)?;
+        }
+
         fs::write(save_file, serialised).expect("erm");
+        self.window.refresh_backups();
+
+        if self.settings.timesheet {
+            self.write_timesheet()?;
+        }
+
+        if self.writeback_mode() == WritebackMode::ReadOnly || self.pending_writeback.is_some() {
+            return Ok(());
+        }
+
+        let file_path = self.file_watcher.as_ref().unwrap().file_path.clone();
+        let files = self.format.serialise(&self.root_node, &file_path);
+
+        if let Err((rejected_path, difference)) = self.format.verify(&self.root_node, &files) {
+            return Err(self.reject_writeback(&files, &rejected_path, &difference));
+        }
+
+        let changed_files: Vec<(PathBuf, String)> = files
+            .into_iter()
+            .filter(|(output_path, contents)| fs::read_to_string(output_path).ok().as_ref() != Some(contents))
+            .collect();
+
+        if changed_files.is_empty() {
+            return Ok(());
+        }
+
+        if self.writeback_mode() == WritebackMode::Confirm {
+            // Changes that were already turned down are not offered again
+            if self.rejected_writeback.as_ref() != Some(&changed_files) {
+                self.window.confirm_writeback(App::diff_files(&changed_files));
+                self.pending_writeback = Some(changed_files);
+            }
+
+            return Ok(());
+        }
+
+        self.write_files(changed_files)
+    }
+}
+
+impl App {
+    /// Regenerates `TIMESHEET.md` next to the task file when its contents have changed.
+    fn write_timesheet(&mut self) -> Result<(), String> {
+        let file_path = &self.file_watcher.as_ref().unwrap().file_path;
+        let timesheet_path = file_path.with_file_name("TIMESHEET.md");
+
+        let contents = timesheet::render_markdown(&self.window.saved_state(), &self.project_name());
+        if fs::read_to_string(&timesheet_path).ok().as_ref() == Some(&contents) {
+            return Ok(());
+        }
+
+        if fs::write(&timesheet_path, contents).is_err() {
+            return Err(format!("Save failed. Could not write {}", timesheet_path.display()));
+        }
+
+        Ok(())
+    }
+
+    fn write_files(&mut self, files: Vec<(PathBuf, String)>) -> Result<(), String> {
+        for (output_path, contents) in files.iter() {
+            let name = self.backup_name(output_path);
+            backups::backup_file(&self.backup_dir(), output_path, &name, self.settings.backup_retention)?;
+
+            if fs::write(output_path, contents).is_err() {
+                return Err(format!("Save failed. Could not write {}", output_path.display()));
+            }
+        }
 
-        let file_name = self.file_watcher.as_ref().unwrap().file_name.clone();
-        markdown_serialiser::export(self.root_node.clone(), file_name);
+        for (output_path, contents) in files {
+            self.written_files.retain(|(written_path, _)| written_path != &output_path);
+            self.written_files.push((output_path, contents));
+        }
+        self.window.refresh_backups();
+        Ok(())
+    }
+
+    /// Saved state with the log left out, or `None` when it is not valid JSON.
+    fn without_logs(save_contents: &str) -> Option<serde_json::Value> {
+        let mut state: serde_json::Value = serde_json::from_str(save_contents).ok()?;
+        state.as_object_mut()?.remove("logger");
+        Some(state)
+    }
+
+    /// The name backups of the task file at `path` are kept under.
+    fn backup_name(&self, path: &Path) -> String {
+        let file_path = &self.file_watcher.as_ref().unwrap().file_path;
+        let project_dir = file_path.parent().unwrap_or(Path::new(""));
+        backups::backup_name(project_dir, path)
+    }
+
+    /// Whether any of the changed task files were edited by something other than this app.
+    /// Each write is expected to show up once, so its entry is dropped when its event arrives.
+    fn has_outside_change(&mut self, changed_paths: &[PathBuf]) -> bool {
+        let mut outside_change = false;
+        for path in changed_paths {
+            let written = self.written_files.iter().position(|(written_path, _)| written_path == path);
+            let Some(idx) = written else {
+                outside_change = true;
+                continue;
+            };
+
+            let (_, contents) = self.written_files.remove(idx);
+            if fs::read_to_string(path).ok() != Some(contents) {
+                outside_change = true;
+            }
+        }
+
+        outside_change
+    }
+
+    /// Puts a backup back in place of the file it was taken from, backing up the current
+    /// version first. Restored task files are picked up by the file watcher.
+    fn restore_backup(&mut self, entry: &BackupEntry) -> Result<(), String> {
+        let Ok(contents) = fs::read_to_string(&entry.path) else {
+            return Err(format!("Restore failed. Could not read {}", entry.path.display()));
+        };
+
+        if entry.original_name == SAVE_BACKUP_NAME {
+            let deserialised: Window = match serde_json::from_str(&contents) {
+                Ok(window) => window,
+                Err(e) => return Err(format!("Restore failed. Invalid save file: {}", e)),
+            };
+
+            let save_file = PathBuf::from(format!("{}/save.json", self.save_dir()));
+            backups::backup_file(&self.backup_dir(), &save_file, SAVE_BACKUP_NAME, self.settings.backup_retention)?;
+            if fs::write(&save_file, &contents).is_err() {
+                return Err(format!("Restore failed. Could not write {}", save_file.display()));
+            }
+
+            self.window.load(deserialised);
+            self.window.refresh_backups();
+            self.root_node = self.window.extract_node();
+            return Ok(());
+        }
+
+        let file_watcher = self.file_watcher.as_ref().unwrap();
+        let mut task_files = std::iter::once(&file_watcher.file_path).chain(file_watcher.included_paths.iter());
+        let Some(target) = task_files.find(|path| self.backup_name(path) == entry.original_name).cloned() else {
+            return Err(format!("Restore failed. {} is not part of this project", entry.original_name));
+        };
+
+        self.write_files(vec![(target.clone(), contents)])?;
 
+        // Unlike a save, the restored file is not in the tree yet, so let the watcher reload it
+        self.written_files.retain(|(written_path, _)| written_path != &target);
         return Ok(());
     }
+
+    fn diff_files(files: &[(PathBuf, String)]) -> String {
+        let mut diff = String::new();
+        for (output_path, contents) in files {
+            let current = fs::read_to_string(output_path).unwrap_or_default();
+            let name = output_path.display().to_string();
+
+            let file_diff = TextDiff::from_lines(&current, contents)
+                .unified_diff()
+                .context_radius(3)
+                .header(&name, &name)
+                .to_string();
+            diff.push_str(&file_diff);
+        }
+
+        diff
+    }
+
+    fn resolve_writeback(&mut self, accepted: bool) -> Result<(), String> {
+        let Some(files) = self.pending_writeback.take() else {
+            return Ok(());
+        };
+
+        if accepted {
+            self.rejected_writeback = None;
+            self.write_files(files)?;
+            self.window
+                .log("Wrote accepted changes to the task file", LogType::INFO(InfoSubType::Save));
+        } else {
+            self.rejected_writeback = Some(files);
+            self.window
+                .log("Discarded changes to the task file", LogType::INFO(InfoSubType::General));
+        }
+
+        Ok(())
+    }
+
+    /// Keeps output that would not read back as the current tree beside the original file
+    /// instead of overwriting it.
+    fn reject_writeback(&mut self, files: &[(PathBuf, String)], rejected_path: &Path, difference: &str) -> String {
+        let mut rejected_file = rejected_path.as_os_str().to_owned();
+        rejected_file.push(".rejected");
+        let rejected_file = PathBuf::from(rejected_file);
+
+        let contents = files
+            .iter()
+            .find(|(output_path, _)| output_path == rejected_path)
+            .map(|(_, contents)| contents.clone())
+            .unwrap_or_default();
+
+        let mut message = format!(
+            "Save aborted. {} would not read back the same. {}",
+            rejected_path.display(),
+            difference
+        );
+
+        if fs::write(&rejected_file, contents).is_err() {
+            message.push_str(" The rejected output could not be written.");
+        } else {
+            message.push_str(&format!(" Output kept in {}", rejected_file.display()));
+        }
+
+        self.window.show_error(&message);
+        message
+    }
 }
 
 impl EventHandler for App {
@@ -0,0 +1,58 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use crate::node::DetachedTask;
+
+/// A completed task that was moved out of the task file into the archive file, kept in the
+/// save so its time is not lost.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ArchivedTask {
+    pub heading_path: Vec<String>,
+    pub name: String,
+    pub time: Duration,
+    pub archived: i64,
+}
+
+impl ArchivedTask {
+    pub fn new(heading_path: &[String], task: &DetachedTask) -> Self {
+        Self {
+            heading_path: heading_path.to_vec(),
+            name: task.name.clone(),
+            time: task.time,
+            archived: Local::now().timestamp(),
+        }
+    }
+}
+
+/// `ARCHIVE.md` next to the task file.
+pub fn archive_file_path(file_path: &Path) -> PathBuf {
+    file_path.with_file_name("ARCHIVE.md")
+}
+
+/// Appends a section dated today to the archive file, listing `tasks` under the headings they
+/// were taken from.
+pub fn append_to_file(archive_path: &Path, tasks: &[(Vec<String>, DetachedTask)]) -> Result<(), String> {
+    let mut contents = fs::read_to_string(archive_path).unwrap_or_default();
+    if !contents.is_empty() && !contents.ends_with("\n\n") {
+        contents.push('\n');
+    }
+
+    contents.push_str(&format!("## Archived {}\n", Local::now().format("%Y-%m-%d")));
+
+    let mut last_headings: Option<&Vec<String>> = None;
+    for (headings, task) in tasks {
+        if last_headings != Some(headings) {
+            let heading = if headings.is_empty() { "(root)".to_string() } else { headings.join(" / ") };
+            contents.push_str(&format!("\n### {}\n", heading));
+            last_headings = Some(headings);
+        }
+
+        contents.push_str(&format!("- [x] {}\n", task.name));
+    }
+
+    fs::write(archive_path, contents).map_err(|e| format!("Could not write {}: {}", archive_path.display(), e))
+}
@@ -0,0 +1,135 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+
+const STAMP_FORMAT: &str = "%Y%m%d-%H%M%S%.3f";
+const STAMP_LEN: usize = "20240101-120000.000".len();
+
+/// The name backups of the saved state are kept under.
+pub const SAVE_BACKUP_NAME: &str = "save.json";
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct BackupEntry {
+    pub path: PathBuf,
+    /// Path of the backed up file relative to the project, as given by `backup_name`
+    pub original_name: String,
+    pub size: u64,
+    pub created: DateTime<Local>,
+}
+
+impl BackupEntry {
+    fn from_path(path: PathBuf) -> Option<Self> {
+        let file_name = path.file_name()?.to_str()?.to_string();
+        let (stamp, original_name) = file_name.split_at_checked(STAMP_LEN)?;
+        let original_name = original_name.strip_prefix('-')?;
+
+        let naive = NaiveDateTime::parse_from_str(stamp, STAMP_FORMAT).ok()?;
+        let created = Local.from_local_datetime(&naive).single()?;
+        let size = fs::metadata(&path).ok()?.len();
+
+        Some(Self {
+            original_name: unescape_name(original_name),
+            path,
+            size,
+            created,
+        })
+    }
+
+    pub fn print_size(&self) -> String {
+        const UNITS: [&str; 3] = ["B", "KB", "MB"];
+
+        let mut size = self.size as f64;
+        let mut unit = 0;
+        while size >= 1024.0 && unit < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit += 1;
+        }
+
+        if unit == 0 {
+            return format!("{} {}", self.size, UNITS[0]);
+        }
+
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// The name backups of `original` are kept under: its path relative to `project_dir`, so
+/// files with the same name in different folders are kept apart, or its full path when it
+/// lies outside the project.
+pub fn backup_name(project_dir: &Path, original: &Path) -> String {
+    let Ok(relative) = original.strip_prefix(project_dir) else {
+        return original.display().to_string();
+    };
+
+    let parts: Vec<String> = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect();
+    parts.join("/")
+}
+
+/// Copies `original` into `backup_dir` under a timestamped `name`, then removes the oldest
+/// backups with the same name beyond `retention`. Nothing is kept when `retention` is zero,
+/// or when the newest backup already has the same contents.
+pub fn backup_file(backup_dir: &Path, original: &Path, name: &str, retention: usize) -> Result<(), String> {
+    if retention == 0 || !original.exists() {
+        return Ok(());
+    }
+
+    let backups: Vec<BackupEntry> = list_backups(backup_dir)
+        .into_iter()
+        .filter(|entry| entry.original_name == name)
+        .collect();
+
+    let contents = fs::read(original).ok();
+    if contents.is_some() && backups.first().is_some_and(|newest| fs::read(&newest.path).ok() == contents) {
+        return Ok(());
+    }
+
+    if fs::create_dir_all(backup_dir).is_err() {
+        return Err("Backup failed. Could not create backup directory".to_string());
+    }
+
+    let stamp = Local::now().format(STAMP_FORMAT);
+    let backup_path = backup_dir.join(format!("{}-{}", stamp, escape_name(name)));
+    if fs::copy(original, &backup_path).is_err() {
+        return Err(format!("Backup failed. Could not copy {}", original.display()));
+    }
+
+    // The new backup is the newest, so the ones listed before it fill the rest of the window
+    let stale: Vec<BackupEntry> = backups.into_iter().skip(retention.saturating_sub(1)).collect();
+
+    for entry in stale {
+        if fs::remove_file(&entry.path).is_err() {
+            return Err(format!("Backup failed. Could not remove {}", entry.path.display()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Every backup in `backup_dir`, newest first.
+pub fn list_backups(backup_dir: &Path) -> Vec<BackupEntry> {
+    let Ok(dir_entries) = fs::read_dir(backup_dir) else {
+        return Vec::new();
+    };
+
+    let mut backups: Vec<BackupEntry> = dir_entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| BackupEntry::from_path(entry.path()))
+        .collect();
+
+    backups.sort_by(|a, b| b.path.cmp(&a.path));
+
+    backups
+}
+
+/// Makes a relative path usable as part of a single file name.
+fn escape_name(name: &str) -> String {
+    name.replace('%', "%25").replace('/', "%2F")
+}
+
+fn unescape_name(name: &str) -> String {
+    name.replace("%2F", "/").replace("%25", "%")
+}
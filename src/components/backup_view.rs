@@ -0,0 +1,133 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    prelude::{Buffer, Constraint, Layout, Rect, Stylize},
+    style::{Color, Style},
+    text::Line,
+    widgets::{Block, Paragraph, Widget},
+};
+
+use crate::{
+    backups::{self, BackupEntry},
+    info_subtype::InfoSubType,
+    traits::ViewEventHandler,
+};
+
+#[derive(Default)]
+pub struct BackupView {
+    pub backup_dir: PathBuf,
+    backups: Vec<BackupEntry>,
+    selected: usize,
+
+    preview: String,
+    preview_scroll: u16,
+
+    pending_restore: Option<BackupEntry>,
+}
+
+impl BackupView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn refresh(&mut self) {
+        self.backups = backups::list_backups(&self.backup_dir);
+        self.select(self.selected.min(self.backups.len().saturating_sub(1)));
+    }
+
+    /// Takes the backup the user asked to restore, if any.
+    pub fn take_restore(&mut self) -> Option<BackupEntry> {
+        self.pending_restore.take()
+    }
+
+    fn select(&mut self, idx: usize) {
+        self.selected = idx;
+        self.preview_scroll = 0;
+        self.preview = match self.backups.get(idx) {
+            Some(entry) => fs::read_to_string(&entry.path).unwrap_or_else(|_| "Could not read backup".to_string()),
+            None => String::new(),
+        };
+    }
+
+    fn request_restore(&mut self) -> Result<(InfoSubType, String), String> {
+        let Some(entry) = self.backups.get(self.selected) else {
+            return Err("No backup selected".to_string());
+        };
+
+        self.pending_restore = Some(entry.clone());
+        Ok((InfoSubType::None, "erm".to_string()))
+    }
+
+    fn render_list(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered().title(" Backups ").border_style(Color::Gray);
+        let inner_area = block.inner(area);
+        block.render(area, buf);
+
+        if self.backups.is_empty() {
+            Line::from("No backups yet").fg(Color::DarkGray).render(inner_area, buf);
+            return;
+        }
+
+        // Keep the selected row on screen
+        let visible_rows = inner_area.height as usize;
+        let first_row = self.selected.saturating_sub(visible_rows.saturating_sub(1));
+
+        for (row, (idx, entry)) in self.backups.iter().enumerate().skip(first_row).take(visible_rows).enumerate() {
+            let row_area = Rect {
+                x: inner_area.x,
+                y: inner_area.y + row as u16,
+                width: inner_area.width,
+                height: 1,
+            };
+
+            let text = format!(
+                "{}  {:>9}  {}",
+                entry.created.format("%Y-%m-%d %H:%M:%S"),
+                entry.print_size(),
+                entry.original_name
+            );
+
+            let mut style = Style::default();
+            if idx == self.selected {
+                style = style.fg(Color::Black).bg(Color::Gray);
+            }
+
+            Line::from(text).style(style).render(row_area, buf);
+        }
+    }
+}
+
+impl ViewEventHandler for BackupView {
+    fn handle_events(&mut self, key_code: KeyCode) -> Result<(InfoSubType, String), String> {
+        match key_code {
+            KeyCode::Char('j') if self.selected + 1 < self.backups.len() => self.select(self.selected + 1),
+            KeyCode::Char('k') if self.selected > 0 => self.select(self.selected - 1),
+            KeyCode::Char('J') => self.preview_scroll = self.preview_scroll.saturating_add(1),
+            KeyCode::Char('K') => self.preview_scroll = self.preview_scroll.saturating_sub(1),
+            KeyCode::Char('R') => self.refresh(),
+            KeyCode::Char('r') => return self.request_restore(),
+            _ => (),
+        }
+
+        Ok((InfoSubType::None, "erm".to_string()))
+    }
+}
+
+impl Widget for &BackupView {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        use Constraint::Percentage;
+
+        let horizontal = Layout::horizontal([Percentage(45), Percentage(55)]);
+        let [list_area, preview_area] = horizontal.areas(area);
+
+        self.render_list(list_area, buf);
+
+        let block = Block::bordered().title(" Preview ").border_style(Color::Gray);
+        Paragraph::new(self.preview.clone())
+            .block(block)
+            .scroll((self.preview_scroll, 0))
+            .render(preview_area, buf);
+    }
+}
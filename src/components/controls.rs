@@ -51,6 +51,7 @@ impl Widget for &ControlSection {
 pub struct Controls {
     main_controls: ControlSection,
     log_controls: ControlSection,
+    backup_controls: ControlSection,
 }
 
 impl Controls {
@@ -65,9 +66,15 @@ impl Controls {
             control_list: Controls::define_log_controls(),
         };
 
+        let backup_controls = ControlSection {
+            title: "Backups".to_string(),
+            control_list: Controls::define_backup_controls(),
+        };
+
         return Self {
             main_controls,
             log_controls,
+            backup_controls,
         };
     }
 
@@ -81,6 +88,28 @@ impl Controls {
             ("␣".to_string(), "Complete Task".to_string()),
             ("s".to_string(), "Toggle Time".to_string()),
             ("b".to_string(), "Ender Parent Heading".to_string()),
+            ("e".to_string(), "Export".to_string()),
+            ("/".to_string(), "Find Task".to_string()),
+            ("v".to_string(), "Switch View".to_string()),
+            ("h".to_string(), "Collapse Heading".to_string()),
+            ("l".to_string(), "Expand Heading".to_string()),
+            ("o".to_string(), "Sort All Tasks".to_string()),
+            ("f".to_string(), "Filter By Status".to_string()),
+            ("t".to_string(), "Filter By Tag".to_string()),
+            ("a".to_string(), "Add Task".to_string()),
+            ("r".to_string(), "Rename Entry".to_string()),
+            ("d".to_string(), "Delete Task".to_string()),
+            ("E".to_string(), "Set Estimate".to_string()),
+            ("T".to_string(), "Adjust Time".to_string()),
+            ("A".to_string(), "Add Subheading".to_string()),
+            ("[".to_string(), "Move Up".to_string()),
+            ("]".to_string(), "Move Down".to_string()),
+            ("x".to_string(), "Cut Task".to_string()),
+            ("p".to_string(), "Paste Task".to_string()),
+            ("c".to_string(), "Hide Completed".to_string()),
+            ("Z".to_string(), "Archive Completed".to_string()),
+            ("I".to_string(), "Import Timewarrior".to_string()),
+            ("U".to_string(), "Review Unmatched".to_string()),
         ];
     }
 
@@ -92,21 +121,37 @@ impl Controls {
             ("L".to_string(), "Next Subfilter".to_string()),
             ("j".to_string(), "Next Log Page".to_string()),
             ("k".to_string(), "Previous Log Page ".to_string()),
+            ("n".to_string(), "Next Entry".to_string()),
+            ("p".to_string(), "Previous Entry".to_string()),
+            ("↵".to_string(), "Jump to Task".to_string()),
         ];
     }
+
+    fn define_backup_controls() -> Vec<(String, String)> {
+        vec![
+            ("j".to_string(), "Next Backup".to_string()),
+            ("k".to_string(), "Previous Backup".to_string()),
+            ("J".to_string(), "Scroll Preview Down".to_string()),
+            ("K".to_string(), "Scroll Preview Up".to_string()),
+            ("r".to_string(), "Restore Backup".to_string()),
+            ("R".to_string(), "Refresh List".to_string()),
+        ]
+    }
 }
 
 impl Widget for &Controls {
     fn render(self, area: Rect, buf: &mut Buffer) {
         assert!(self.main_controls.control_list.len() > 0);
         assert!(self.log_controls.control_list.len() > 0);
+        assert!(!self.backup_controls.control_list.is_empty());
 
         use Constraint::{Min, Percentage};
 
-        let vertical = Layout::vertical([Percentage(20), Percentage(20), Min(0)]);
-        let [main_area, log_area, _] = vertical.areas(area);
+        let vertical = Layout::vertical([Percentage(20), Percentage(20), Percentage(20), Min(0)]);
+        let [main_area, log_area, backup_area, _] = vertical.areas(area);
 
         self.main_controls.render(main_area, buf);
         self.log_controls.render(log_area, buf);
+        self.backup_controls.render(backup_area, buf);
     }
 }
@@ -3,7 +3,7 @@ use crate::log_type::LogType;
 use crossterm::event::KeyCode;
 use ratatui::{
     prelude::{Buffer, Constraint, Layout, Rect, Stylize},
-    style::Color,
+    style::{Color, Style},
     text::Line,
     widgets::{Tabs, Widget},
 };
@@ -13,7 +13,7 @@ use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 use strum_macros::{Display, EnumIter, FromRepr};
 
-use crate::{info_subtype::InfoSubType, traits::ViewEventHandler};
+use crate::{diagnostic::JumpTarget, info_subtype::InfoSubType, traits::ViewEventHandler};
 
 use super::Paginator;
 
@@ -24,6 +24,8 @@ pub enum Filter {
     ALL,
     #[strum(to_string = "Info")]
     INFO,
+    #[strum(to_string = "Warning")]
+    Warning,
     #[strum(to_string = "Error")]
     ERROR,
 }
@@ -46,17 +48,12 @@ impl Filter {
     }
 
     pub fn includes(self, log_type: LogType) -> bool {
-        let mut includes_filter = false;
-
-        if self == Filter::ALL {
-            includes_filter = true;
-        } else if matches!(log_type, LogType::INFO(_)) && self == Filter::INFO {
-            includes_filter = true;
-        } else if log_type == LogType::ERROR && self == Filter::ERROR {
-            includes_filter = true;
+        match self {
+            Filter::ALL => true,
+            Filter::INFO => matches!(log_type, LogType::INFO(_)),
+            Filter::Warning => log_type == LogType::Warning,
+            Filter::ERROR => log_type == LogType::ERROR,
         }
-
-        return includes_filter;
     }
 }
 
@@ -81,6 +78,8 @@ pub struct LogEntry {
     pub log_type: LogType,
     pub time_stamp: TimeStamp,
     pub message: String,
+    #[serde(default)]
+    pub target: Option<JumpTarget>,
 }
 
 impl Widget for &LogEntry {
@@ -148,6 +147,10 @@ impl TimeStamp {
         };
     }
 
+    pub fn date(&self) -> String {
+        format!("{} {}", self.day, self.month)
+    }
+
     pub fn print(&self) -> String {
         return format!(
             "{} {}: {:02}:{:02}:{:02}",
@@ -164,6 +167,11 @@ pub struct LogView {
     selected_subfilter: Option<SubFilter>,
     available_subfilters: Vec<SubFilter>,
     paginator: Paginator,
+
+    #[serde(default)]
+    selected_log: Option<usize>,
+    #[serde(skip)]
+    pending_jump: Option<JumpTarget>,
 }
 
 impl LogView {
@@ -179,6 +187,9 @@ impl LogView {
                 page_size: 8,
                 entry_len: 0,
             },
+
+            selected_log: None,
+            pending_jump: None,
         };
     }
 
@@ -187,19 +198,35 @@ impl LogView {
     }
 
     pub fn log(&mut self, message: &str, log_type: LogType) {
-        self.logs.push(LogEntry {
+        self.log_with_target(message, log_type, None);
+    }
+
+    pub fn log_with_target(&mut self, message: &str, log_type: LogType, target: Option<JumpTarget>) {
+        self.push_entry(LogEntry {
             log_type,
             time_stamp: TimeStamp::new(),
             message: message.to_string(),
+            target,
         });
+    }
+
+    /// Adds an entry that was already timestamped, such as one logged before a save was loaded.
+    pub fn push_entry(&mut self, entry: LogEntry) {
+        self.logs.push(entry);
 
         self.paginator.entry_len = self.logs.len();
 
         if self.logs.len() >= 100 {
             self.logs.remove(0);
+            self.selected_log = self.selected_log.and_then(|idx| idx.checked_sub(1));
         }
     }
 
+    /// Takes the location requested by the last jump, if any.
+    pub fn take_jump(&mut self) -> Option<JumpTarget> {
+        self.pending_jump.take()
+    }
+
     pub fn recent(&self) -> Vec<LogEntry> {
         const SIZE: usize = 15;
 
@@ -309,6 +336,50 @@ impl LogView {
         }
     }
 
+    fn is_visible(&self, entry: &LogEntry) -> bool {
+        if !self.selected_filter.includes(entry.log_type) {
+            return false;
+        }
+
+        if let (Some(subfilter), LogType::INFO(subtype)) = (self.selected_subfilter, entry.log_type) {
+            return subtype == subfilter.info_type;
+        }
+
+        true
+    }
+
+    /// Moves the selection through visible entries in the order they are drawn, which is
+    /// newest first on each page.
+    fn select_entry(&mut self, older: bool) {
+        let visible: Vec<usize> = (0..self.logs.len()).filter(|&idx| self.is_visible(&self.logs[idx])).collect();
+        if visible.is_empty() {
+            self.selected_log = None;
+            return;
+        }
+
+        let next = match self.selected_log.and_then(|idx| visible.iter().position(|&v| v == idx)) {
+            Some(pos) if older => visible[pos.saturating_sub(1)],
+            Some(pos) => visible[(pos + 1).min(visible.len() - 1)],
+            None => *visible.last().unwrap(),
+        };
+
+        self.selected_log = Some(next);
+        self.paginator.page = next / self.paginator.page_size;
+    }
+
+    fn jump_to_selected(&mut self) -> Result<(InfoSubType, String), String> {
+        let Some(entry) = self.selected_log.and_then(|idx| self.logs.get(idx)) else {
+            return Err("No log entry selected".to_string());
+        };
+
+        let Some(target) = entry.target.clone() else {
+            return Err("Selected log entry has no task to jump to".to_string());
+        };
+
+        self.pending_jump = Some(target);
+        Ok((InfoSubType::None, "erm".to_string()))
+    }
+
     fn render_log_page(&self, area: Rect, buf: &mut Buffer) {
         let (start_idx, end_idx) = self.paginator.page_slice();
 
@@ -318,19 +389,11 @@ impl LogView {
 
         const ENTRY_HEIGHT: u16 = 2;
         let mut total_height = 1;
-        for entry in self.logs[start_idx..end_idx].iter().rev() {
-            if !self.selected_filter.includes(entry.log_type) {
+        for (idx, entry) in self.logs[start_idx..end_idx].iter().enumerate().rev() {
+            if !self.is_visible(entry) {
                 continue;
             }
 
-            if self.selected_subfilter.is_some() {
-                if let LogType::INFO(subtype) = entry.log_type {
-                    if subtype != self.selected_subfilter.unwrap().info_type {
-                        continue;
-                    }
-                }
-            }
-
             let entry_area = Rect {
                 x: area.x,
                 y: area.y + total_height,
@@ -339,6 +402,9 @@ impl LogView {
             };
 
             entry.render(entry_area, buf);
+            if self.selected_log == Some(start_idx + idx) {
+                buf.set_style(entry_area, Style::default().bg(Color::DarkGray));
+            }
             total_height += entry_area.height;
 
             let separator_area = Rect {
@@ -364,6 +430,9 @@ impl ViewEventHandler for LogView {
             KeyCode::Char('L') => self.next_subfilter(),
             KeyCode::Char('j') => self.paginator.next_page(),
             KeyCode::Char('k') => self.paginator.prev_page(),
+            KeyCode::Char('n') => self.select_entry(true),
+            KeyCode::Char('p') => self.select_entry(false),
+            KeyCode::Enter => return self.jump_to_selected(),
             _ => (),
         }
 
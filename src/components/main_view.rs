@@ -1,6 +1,6 @@
 use ratatui::{
     prelude::{
-        Buffer, Constraint,
+        Alignment, Buffer, Constraint,
         Constraint::{Length, Min},
         Layout, Rect, Stylize,
     },
@@ -9,13 +9,21 @@ use ratatui::{
     widgets::Widget,
 };
 
+use chrono::Local;
 use crossterm::event::KeyCode;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
 use crate::{
+    archive::ArchivedTask,
+    diagnostic::JumpTarget,
     info_subtype::InfoSubType,
-    node::{Node, NodePath},
+    node::{DetachedTask, Node, NodePath, TaskMetadata},
+    reports::{
+        saved_state::heading_text,
+        timewarrior::{self, Interval, IntervalMatch},
+    },
+    session::{Adjustment, Completion, Session},
     traits::ViewEventHandler,
 };
 
@@ -26,27 +34,95 @@ pub struct Task {
     pub name: String,
     pub duration: Duration,
     pub completed: bool,
+    /// Completed and total tasks under a heading row, shown after its name
+    #[serde(default)]
+    pub progress: Option<(usize, usize)>,
+    /// Time the task or heading was expected to take, shown against the time spent
+    #[serde(default)]
+    pub estimate: Option<Duration>,
+    /// Whether the timer is running on this task, which counts down its estimate
+    #[serde(default)]
+    pub active: bool,
+    /// Priority and `key:value` fields read from a todo.txt line, shown around its name
+    #[serde(default)]
+    pub metadata: TaskMetadata,
 
     pub style: Style,
 }
 
-impl Task {
-    fn format_duration(&self) -> String {
-        let secs = self.duration.as_secs();
-        let hours = secs / 3600;
-        let minutes = (secs % 3600) / 60;
-        let seconds = secs % 60;
-        format!("[{:02}:{:02}:{:02}]", hours, minutes, seconds)
+/// Longest time a task can be set to by hand, far short of where adding up task times overflows
+const MAX_ADJUSTED_TIME: Duration = Duration::from_secs(10_000 * 3600);
+
+/// Reads an estimate typed into the estimate popup, where `none` clears it.
+fn parse_estimate(text: &str) -> Result<Option<Duration>, String> {
+    let text = text.trim();
+    if text.eq_ignore_ascii_case("none") {
+        return Ok(None);
+    }
+
+    match Node::parse_duration(text) {
+        Some(estimate) => Ok(Some(estimate)),
+        None => Err(format!("'{}' is not an estimate like 2h30m, 45m or 90s", text)),
     }
 }
 
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let hours = secs / 3600;
+    let minutes = (secs % 3600) / 60;
+    let seconds = secs % 60;
+    format!("[{:02}:{:02}:{:02}]", hours, minutes, seconds)
+}
+
+/// A bar of `width` cells filled in proportion to the completed tasks, with the counts and
+/// percentage after it, e.g. `███░░░░░ 3/8 37%`.
+pub fn format_progress(completed: usize, total: usize, width: usize) -> String {
+    let filled = completed * width / total.max(1);
+    let percent = completed * 100 / total.max(1);
+
+    let bar = "█".repeat(filled) + &"░".repeat(width - filled);
+    format!("{} {}/{} {}%", bar, completed, total, percent)
+}
+
 impl Widget for &Task {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let duration = self.format_duration();
+        let mut spans = match self.estimate {
+            Some(estimate) => {
+                let actual = format_duration(self.duration);
+                let expected = format_duration(estimate);
+                let time = format!("{}/{}", actual.trim_end_matches(']'), expected.trim_start_matches('['));
+
+                let over = self.duration > estimate;
+                vec![if over { Span::raw(time).fg(Color::Red) } else { Span::raw(time) }]
+            }
+            None => vec![Span::raw(format_duration(self.duration))],
+        };
+        // The estimate is already shown beside the time
+        let name = if self.estimate.is_some() { Node::without_estimate(&self.name) } else { self.name.clone() };
+        if let Some(priority) = self.metadata.priority {
+            spans.push(Span::raw(format!(" ({})", priority)).fg(Color::Yellow));
+        }
+        spans.push(Span::raw(format!(" {}", name)));
+        for (key, value) in self.metadata.fields.iter() {
+            spans.push(Span::raw(format!(" {}:{}", key, value)).fg(Color::DarkGray));
+        }
 
-        let task = format!("{} {}", duration, self.name);
+        if let Some((completed, total)) = self.progress.filter(|(_, total)| *total > 0) {
+            spans.push(Span::raw(format!("  {}", format_progress(completed, total, 8))));
+        }
 
-        Line::from(task).style(self.style).render(area, buf);
+        if let Some(estimate) = self.estimate.filter(|_| self.active) {
+            let countdown = if self.duration > estimate {
+                let over = format_duration(self.duration - estimate);
+                Span::raw(format!("  {} over", over.trim_matches(['[', ']']))).fg(Color::Red)
+            } else {
+                let left = format_duration(estimate - self.duration);
+                Span::raw(format!("  {} left", left.trim_matches(['[', ']'])))
+            };
+            spans.push(countdown);
+        }
+
+        Line::from(spans).style(self.style).render(area, buf);
     }
 }
 
@@ -61,14 +137,23 @@ pub struct Tasks {
     pub total_time: Duration,
     pub active_time: Option<u16>,
 
+    /// Index into the displayed node's children for each subheading row
+    #[serde(default)]
+    pub subheading_indices: Vec<usize>,
+    /// Index into the displayed node's content for each task row
+    #[serde(default)]
+    pub task_indices: Vec<usize>,
+
     page_start: usize,
     page_end: usize,
 }
 
 impl Tasks {
-    pub fn new(node: &Node) -> Self {
-        let tasks = Tasks::extract_tasks(node);
-        let subheadings = Tasks::subheading_times(node);
+    /// Builds the rows for `node`, leaving out completed tasks and fully completed subheadings
+    /// when `hide_completed` is set.
+    pub fn new(node: &Node, hide_completed: bool) -> Self {
+        let (tasks, task_indices) = Tasks::extract_tasks(node, hide_completed);
+        let (subheadings, subheading_indices) = Tasks::subheading_times(node, hide_completed);
 
         let task_offset = tasks.len();
 
@@ -82,6 +167,9 @@ impl Tasks {
             total_time: Duration::from_secs(0),
             active_time: None,
 
+            subheading_indices,
+            task_indices,
+
             page_start: 0,
             page_end: 0,
         };
@@ -101,46 +189,68 @@ impl Tasks {
             }
 
             entry.style = style;
+            entry.active = self.active_time == Some((self.page_start + idx) as u16);
         }
     }
 
-    fn extract_tasks(node: &Node) -> Vec<Task> {
+    fn extract_tasks(node: &Node, hide_completed: bool) -> (Vec<Task>, Vec<usize>) {
         let mut tasks: Vec<Task> = Vec::new();
+        let mut indices: Vec<usize> = Vec::new();
 
         for idx in 0..node.completed_tasks.len() {
+            if hide_completed && node.completed_tasks[idx] {
+                continue;
+            }
+
             let name = node.content[idx].clone();
             let completed = node.completed_tasks[idx];
             let duration = node.content_times[idx];
             let style = Style::default();
 
             let task = Task {
+                estimate: Node::estimate(&name),
                 name,
                 completed,
                 duration,
+                progress: None,
+                active: false,
+                metadata: node.task_metadata(idx),
                 style,
             };
 
             tasks.push(task);
+            indices.push(idx);
         }
 
-        return tasks;
+        (tasks, indices)
     }
 
-    fn subheading_times(node: &Node) -> Vec<Task> {
+    fn subheading_times(node: &Node, hide_completed: bool) -> (Vec<Task>, Vec<usize>) {
         let mut entries: Vec<Task> = Vec::new();
-        for subheading in node.children.iter() {
+        let mut indices: Vec<usize> = Vec::new();
+        for (idx, subheading) in node.children.iter().enumerate() {
+            if subheading.excluded {
+                continue;
+            }
+
+            let (completed, total) = subheading.completion_counts();
+            if hide_completed && total > 0 && completed == total {
+                continue;
+            }
+
             let full_entry = Tasks::extract_entry(&subheading);
             entries.push(full_entry);
+            indices.push(idx);
         }
-        return entries;
+        (entries, indices)
     }
 
-    fn extract_entry(node: &Node) -> Task {
+    pub fn extract_entry(node: &Node) -> Task {
         let completed_node = node.completed_tasks.iter().all(|&x| x);
         let mut completed_subheadings = true;
 
         let mut entry_time: Duration = node.content_times.iter().sum();
-        for subheading in node.children.iter() {
+        for subheading in node.children.iter().filter(|child| !child.excluded) {
             let entry = Tasks::extract_entry(subheading);
 
             entry_time += entry.duration;
@@ -151,6 +261,10 @@ impl Tasks {
             name: node.heading.clone().unwrap(),
             duration: entry_time,
             completed: completed_node && completed_subheadings,
+            progress: Some(node.completion_counts()),
+            estimate: node.estimate_totals().map(|(estimate, _)| estimate),
+            active: false,
+            metadata: TaskMetadata::default(),
             style: Style::default(),
         };
 
@@ -254,7 +368,10 @@ impl Widget for &Tasks {
 #[derive(Serialize, Deserialize, Default, Clone)]
 pub struct NavigationBar {
     back_text: String,
-    breadcrumbs: Vec<String>,
+    pub breadcrumbs: Vec<String>,
+    /// Completed and total tasks under the displayed heading
+    #[serde(default)]
+    pub progress: (usize, usize),
 }
 
 impl NavigationBar {
@@ -262,26 +379,34 @@ impl NavigationBar {
         return Self {
             back_text: " (b) Back ".to_string(),
             breadcrumbs: Vec::new(),
+            progress: (0, 0),
         };
     }
 
     pub fn push_breadcrumb(&mut self, new_heading: String) {
-        let heading = new_heading.trim_start_matches('#').trim().to_string();
-        self.breadcrumbs.push(heading);
+        self.breadcrumbs.push(heading_text(&new_heading));
     }
 
     pub fn pop_breadcrumb(&mut self) {
         self.breadcrumbs.pop();
     }
+
+    pub fn clear_breadcrumbs(&mut self) {
+        self.breadcrumbs.clear();
+    }
 }
 
 impl Widget for &NavigationBar {
     fn render(self, area: Rect, buf: &mut Buffer) {
         use Constraint::{Length, Min};
 
+        let (completed, total) = self.progress;
+        let progress = if total > 0 { format_progress(completed, total, 10) } else { String::new() };
+
         let back_len = self.back_text.len() as u16;
-        let horizontal = Layout::horizontal([Length(back_len), Length(5), Min(0)]);
-        let [back_area, _, breadcrumb_area] = horizontal.areas(area);
+        let progress_len = progress.chars().count() as u16;
+        let horizontal = Layout::horizontal([Length(back_len), Length(5), Min(0), Length(progress_len)]);
+        let [back_area, _, breadcrumb_area, progress_area] = horizontal.areas(area);
 
         Line::from(self.back_text.clone())
             .fg(Color::Black)
@@ -308,6 +433,7 @@ impl Widget for &NavigationBar {
         }
 
         Line::from(breadcrumb_content).render(breadcrumb_area, buf);
+        Line::from(progress).fg(Color::Blue).render(progress_area, buf);
     }
 }
 
@@ -324,6 +450,30 @@ pub struct TaskView {
     selected_line: u16,
 
     nav_bar: NavigationBar,
+
+    #[serde(default)]
+    pub sessions: Vec<Session>,
+    /// Task line and start time of the timer that is currently running
+    #[serde(skip)]
+    open_session: Option<(usize, i64)>,
+    /// Task that has been cut and not pasted yet. It is no longer in the file, so the save
+    /// is the only place its time is kept.
+    #[serde(default)]
+    clipboard: Option<DetachedTask>,
+    /// Line of a timer that an edit stopped, waiting to be logged
+    #[serde(skip)]
+    stopped_timer: Option<usize>,
+    #[serde(default)]
+    pub hide_completed: bool,
+    /// Tasks moved out to the archive file, with the time tracked on them
+    #[serde(default)]
+    pub archived: Vec<ArchivedTask>,
+    /// Times set by hand on tasks, so their tracked time can still be accounted for
+    #[serde(default)]
+    pub adjustments: Vec<Adjustment>,
+    /// When each completed task was checked off
+    #[serde(default)]
+    pub completions: Vec<Completion>,
 }
 
 impl TaskView {
@@ -344,6 +494,15 @@ impl TaskView {
             selected_line: 1,
 
             nav_bar: NavigationBar::new(),
+
+            sessions: Vec::new(),
+            open_session: None,
+            clipboard: None,
+            stopped_timer: None,
+            hide_completed: false,
+            archived: Vec::new(),
+            adjustments: Vec::new(),
+            completions: Vec::new(),
         };
     }
 
@@ -353,23 +512,38 @@ impl TaskView {
             content_area: Rect::default(),
 
             displayed_node: task_view.displayed_node,
-            tasks: task_view.tasks.clone(),
+            // No session is open yet, so no timer can still be running
+            tasks: Tasks {
+                active_time: None,
+                ..task_view.tasks.clone()
+            },
 
             paginator: task_view.paginator,
             content_height: task_view.tasks.content_height,
             selected_line: task_view.selected_line,
 
             nav_bar: task_view.nav_bar,
+
+            sessions: task_view.sessions,
+            open_session: None,
+            clipboard: task_view.clipboard,
+            stopped_timer: None,
+            hide_completed: task_view.hide_completed,
+            archived: task_view.archived,
+            adjustments: task_view.adjustments,
+            completions: task_view.completions,
         };
     }
 
     pub fn update(&mut self) {
         self.tasks.update(self.selected_line);
         self.content_height = self.paginator.content_height();
+        self.nav_bar.progress = self.displayed_node.completion_counts();
     }
 
     pub fn update_display_data(&mut self, new_display_node: Node) {
-        self.tasks = Tasks::new(&new_display_node);
+        self.close_session();
+        self.tasks = Tasks::new(&new_display_node, self.hide_completed);
 
         self.selected_line = 1;
         self.displayed_node = new_display_node;
@@ -382,13 +556,10 @@ impl TaskView {
 
     pub fn get_subheading(&self, line_num: usize) -> Option<Node> {
         let global_idx = self.paginator.offset() + line_num - 1;
-        let subheading_idx = global_idx - self.tasks.task_offset;
+        let subheading_idx = global_idx.checked_sub(self.tasks.task_offset)?;
 
-        if global_idx >= self.tasks.task_offset && subheading_idx < self.displayed_node.children.len() {
-            return Some(self.displayed_node.children[subheading_idx].clone());
-        } else {
-            return None;
-        }
+        let child_idx = *self.tasks.subheading_indices.get(subheading_idx)?;
+        self.displayed_node.children.get(child_idx).cloned()
     }
 
     pub fn update_time(&mut self) -> Result<(), String> {
@@ -399,14 +570,13 @@ impl TaskView {
             Err(e) => return Err(e),
         };
 
-        let mut total_time = Duration::default();
-
         let task_slice = self.tasks.task_slice();
-        for (idx, entry) in task_slice.iter().enumerate() {
-            total_time += entry.duration.clone();
-            self.displayed_node.content_times[idx] = entry.duration.clone();
+        for (row, entry) in task_slice.iter().enumerate() {
+            self.displayed_node.content_times[self.tasks.task_indices[row]] = entry.duration;
         }
 
+        let mut total_time: Duration = self.displayed_node.content_times.iter().sum();
+
         for subheading in self.displayed_node.children.iter() {
             total_time += subheading.total_time;
         }
@@ -416,12 +586,157 @@ impl TaskView {
         return self.root_node.update_node(&node_path, &self.displayed_node);
     }
 
+    pub fn toggle_timer(&mut self) -> Result<(InfoSubType, String), String> {
+        let res = self.tasks.try_activate();
+
+        match (&res, self.tasks.active_time) {
+            (Ok((InfoSubType::StartTimer, _)), Some(task_idx)) => {
+                self.open_session = Some((task_idx as usize, Local::now().timestamp()));
+            }
+            (Ok((InfoSubType::StopTimer, _)), _) => self.close_session(),
+            _ => (),
+        }
+
+        res
+    }
+
+    /// Takes the line of a timer that was stopped by an edit, if there was one.
+    pub fn take_stopped_timer(&mut self) -> Option<usize> {
+        self.stopped_timer.take()
+    }
+
+    /// Where the task with the running timer is in the tree.
+    pub fn running_task(&self) -> Option<JumpTarget> {
+        let row = self.tasks.active_time.map(|idx| idx as usize).filter(|&idx| idx < self.tasks.task_offset)?;
+        let path = Node::find_path(&self.root_node, &self.displayed_node).ok()?;
+
+        Some(JumpTarget { path, task: Some(self.tasks.task_indices[row]) })
+    }
+
+    /// Stops the timer on the task at `target` if it is running, otherwise displays that task
+    /// and starts its timer, stopping any other.
+    pub fn toggle_timer_at(&mut self, target: &JumpTarget) -> Result<(InfoSubType, String), String> {
+        if self.running_task().as_ref() != Some(target) {
+            self.update_time()?;
+            self.jump_to(target)?;
+        }
+
+        self.toggle_timer()
+    }
+
+    /// Records the running timer as a finished session.
+    pub fn close_session(&mut self) {
+        let Some((task_idx, start)) = self.open_session.take() else {
+            return;
+        };
+
+        let end = Local::now().timestamp();
+        if end <= start || task_idx >= self.tasks.task_offset {
+            return;
+        }
+
+        self.sessions.push(Session {
+            heading_path: self.nav_bar.breadcrumbs.clone(),
+            task: Node::without_estimate(&self.tasks.lines[task_idx].name),
+            start,
+            end,
+        });
+    }
+
+    /// Adds each interval's time to the task it matches and records it as a session. Returns how
+    /// many were imported and the intervals that match no task. Intervals that were already
+    /// recorded are skipped.
+    pub fn import_intervals(&mut self, intervals: Vec<Interval>) -> Result<(usize, Vec<Interval>), String> {
+        self.ensure_timer_stopped()?;
+
+        let mut imported = 0;
+        let mut unmatched = Vec::new();
+
+        for interval in intervals {
+            let Some(found) = timewarrior::match_interval(&self.root_node, &interval) else {
+                unmatched.push(interval);
+                continue;
+            };
+
+            if self.attach_interval(&interval, found) {
+                imported += 1;
+            }
+        }
+
+        self.root_node.refresh_total_time();
+        self.nav_bar.clear_breadcrumbs();
+        self.update_display_data(self.root_node.clone());
+
+        Ok((imported, unmatched))
+    }
+
+    /// Adds the time of an interval that matched no task to the task at `target`, picked by hand.
+    pub fn assign_interval(&mut self, interval: &Interval, target: &JumpTarget) -> Result<(InfoSubType, String), String> {
+        self.ensure_timer_stopped()?;
+        self.update_root()?;
+
+        let Some(task_idx) = target.task else {
+            return Err("Timewarrior intervals can only be added to a task".to_string());
+        };
+        let Some((heading_path, _, _)) = self
+            .root_node
+            .task_locations()
+            .into_iter()
+            .find(|(_, path, idx)| *path == target.path && *idx == task_idx)
+        else {
+            return Err("Task is no longer present in the task tree".to_string());
+        };
+
+        let found = IntervalMatch { heading_path, node_path: target.path.clone(), task_idx };
+        if !self.attach_interval(interval, found) {
+            return Err("That interval is already recorded on the task".to_string());
+        }
+
+        self.root_node.refresh_total_time();
+        self.update_display_data(self.root_node.get_node(&target.path).unwrap().clone());
+        self.jump_to(target)?;
+
+        let task_name = self.root_node.get_node(&target.path).unwrap().content[task_idx].clone();
+        Ok((InfoSubType::AssignInterval, task_name))
+    }
+
+    /// Records an interval as a session on the task at `found` and adds its time, unless the
+    /// same session is already recorded. Returns whether it was added.
+    fn attach_interval(&mut self, interval: &Interval, found: IntervalMatch) -> bool {
+        let node = self.root_node.get_node_mut(&found.node_path).unwrap();
+        let session = Session {
+            heading_path: found.heading_path,
+            task: Node::without_estimate(&node.content[found.task_idx]),
+            start: interval.start,
+            end: interval.end,
+        };
+
+        if self.sessions.contains(&session) {
+            return false;
+        }
+
+        node.content_times[found.task_idx] += Duration::from_secs(session.duration_secs());
+        self.sessions.push(session);
+        true
+    }
+
+    /// Imported time is added straight to the tree, which would be lost under a running timer.
+    fn ensure_timer_stopped(&self) -> Result<(), String> {
+        if self.tasks.active_time.is_some() {
+            return Err("Stop the timer before adding Timewarrior intervals".to_string());
+        }
+        Ok(())
+    }
+
     pub fn toggle_task(&mut self) -> Result<(InfoSubType, String), String> {
         let idx = self.paginator.offset() + (self.selected_line as usize - 1);
 
         let info_type: InfoSubType;
         if idx < self.tasks.task_offset {
             info_type = self.tasks.toggle_task(idx);
+            if self.tasks.active_time.is_none() {
+                self.close_session();
+            }
             if let Err(e) = self.update_root() {
                 return Err(e);
             }
@@ -430,9 +745,423 @@ impl TaskView {
         }
 
         let task_name = self.tasks.lines[idx].name.clone();
+        self.record_completion(&task_name, info_type == InfoSubType::CompleteTask);
         return Ok((info_type, task_name));
     }
 
+    /// Keeps the time a task under the displayed heading was checked off, for reports.
+    fn record_completion(&mut self, task_name: &str, completed: bool) {
+        let heading_path = self.nav_bar.breadcrumbs.clone();
+        let task = Node::without_estimate(task_name);
+        self.completions
+            .retain(|completion| completion.heading_path != heading_path || completion.task != task);
+
+        if completed {
+            self.completions.push(Completion { heading_path, task, at: Local::now().timestamp() });
+        }
+    }
+
+    fn toggle_hide_completed(&mut self) -> Result<(InfoSubType, String), String> {
+        self.update_time()?;
+        let node_path = self.update_root()?;
+
+        self.hide_completed = !self.hide_completed;
+        self.update_display_data(self.root_node.get_node(&node_path).unwrap().clone());
+
+        let state = if self.hide_completed { "Hiding" } else { "Showing" };
+        Ok((InfoSubType::HideCompleted, state.to_string()))
+    }
+
+    /// Takes every completed task out of the tree, apart from those already archived, and
+    /// returns them with the headings they were under. With `into_section` they are moved
+    /// under the archive heading, otherwise they are kept in `archived` with their time.
+    /// The tree is left as it was when `write_archive` fails.
+    pub fn archive_completed(
+        &mut self,
+        into_section: bool,
+        write_archive: impl FnOnce(&[(Vec<String>, DetachedTask)]) -> Result<(), String>,
+    ) -> Result<Vec<(Vec<String>, DetachedTask)>, String> {
+        self.update_time()?;
+        let node_path = self.update_root()?;
+
+        let mut tree = self.root_node.clone();
+        let archived = tree.take_completed();
+        if archived.is_empty() {
+            return Err("No completed tasks to archive".to_string());
+        }
+
+        write_archive(&archived)?;
+
+        if into_section {
+            let archive = tree.archive_heading()?;
+            for (_, task) in archived.iter() {
+                archive.push_task(task.clone());
+            }
+        } else {
+            for (headings, task) in archived.iter() {
+                self.archived.push(ArchivedTask::new(headings, task));
+            }
+        }
+
+        self.root_node = tree;
+        self.root_node.refresh_total_time();
+        self.update_display_data(self.root_node.get_node(&node_path).unwrap().clone());
+
+        Ok(archived)
+    }
+
+    /// Swaps in a reparsed tree, keeping the time tracked on tasks that are still present and
+    /// staying under the displayed heading while it still exists. A running timer keeps running
+    /// when its task is still there under the same headings.
+    pub fn replace_tree(&mut self, mut new_root: Node) {
+        // Times are already in the tree as of the last tick, only completions need copying in
+        let _ = self.update_root();
+        let node_path = Node::find_path(&self.root_node, &self.displayed_node).unwrap_or_default();
+
+        let running = self.running_task().and_then(|target| {
+            let task_idx = target.task?;
+            let (heading_path, _, _) = self
+                .root_node
+                .task_locations()
+                .into_iter()
+                .find(|(_, path, idx)| *path == target.path && *idx == task_idx)?;
+            let name = Node::without_estimate(&self.root_node.get_node(&target.path)?.content[task_idx]);
+            Some((heading_path, name))
+        });
+
+        new_root.carry_times_from(&self.root_node);
+
+        let resumed = running.and_then(|(heading_path, name)| {
+            new_root.task_locations().into_iter().find(|(headings, path, idx)| {
+                *headings == heading_path
+                    && new_root.get_node(path).is_some_and(|node| {
+                        Node::without_estimate(&node.content[*idx]) == name && !node.completed_tasks[*idx]
+                    })
+            })
+        });
+
+        // The session only carries on when its task does, otherwise it ends with the old tree
+        let open_session = match resumed {
+            Some(_) => self.open_session.take(),
+            None => {
+                self.close_session();
+                None
+            }
+        };
+
+        let same_heading = new_root
+            .get_node(&node_path)
+            .is_some_and(|node| node.heading == self.displayed_node.heading);
+        let path = match &resumed {
+            Some((_, running_path, _)) => running_path.clone(),
+            None if same_heading => node_path,
+            None => NodePath::new(),
+        };
+
+        self.root_node = new_root;
+        self.update_display_data(self.root_node.get_node(&path).unwrap().clone());
+
+        let target = JumpTarget { path, task: resumed.map(|(_, _, idx)| idx) };
+        let _ = self.jump_to(&target);
+
+        let running_row = target
+            .task
+            .and_then(|task_idx| self.tasks.task_indices.iter().position(|idx| *idx == task_idx));
+        if let Some(row) = running_row {
+            self.tasks.active_time = Some(row as u16);
+            self.open_session = open_session.map(|(_, start)| (row, start));
+        }
+    }
+
+    /// Index and name of the task on the selected line, if it is a task rather than a heading.
+    pub fn selected_task(&self) -> Option<(usize, String)> {
+        let idx = self.paginator.offset() + (self.selected_line as usize - 1);
+        if idx >= self.tasks.task_offset {
+            return None;
+        }
+
+        Some((self.tasks.task_indices[idx], self.tasks.lines[idx].name.clone()))
+    }
+
+    /// Index into the displayed node's children and text of the heading on the selected line.
+    pub fn selected_subheading(&self) -> Option<(usize, String)> {
+        let idx = self.paginator.offset() + (self.selected_line as usize - 1);
+        let row = idx.checked_sub(self.tasks.task_offset)?;
+        let child_idx = *self.tasks.subheading_indices.get(row)?;
+
+        let heading = self.displayed_node.children[child_idx].heading.clone().unwrap_or_default();
+        Some((child_idx, heading.trim_start_matches(['#', '*']).trim().to_string()))
+    }
+
+    /// Every heading and task outside excluded subtrees, with the breadcrumb path above it and
+    /// where to jump to reach it.
+    pub fn search_entries(&self) -> Vec<(String, String, JumpTarget)> {
+        fn collect(node: &Node, headings: &mut Vec<String>, path: &mut NodePath, out: &mut Vec<(String, String, JumpTarget)>) {
+            let breadcrumbs = headings.join(" / ");
+            for (idx, name) in node.content.iter().enumerate() {
+                let target = JumpTarget { path: path.clone(), task: Some(idx) };
+                out.push((name.clone(), breadcrumbs.clone(), target));
+            }
+
+            for (child_idx, child) in node.children.iter().enumerate() {
+                if child.excluded {
+                    continue;
+                }
+
+                let heading = heading_text(child.heading.as_deref().unwrap_or_default());
+                path.push(child_idx);
+                out.push((heading.clone(), breadcrumbs.clone(), JumpTarget { path: path.clone(), task: None }));
+
+                headings.push(heading);
+                collect(child, headings, path, out);
+                headings.pop();
+                path.pop();
+            }
+        }
+
+        let mut entries = Vec::new();
+        collect(&self.root_node, &mut Vec::new(), &mut Vec::new(), &mut entries);
+        entries
+    }
+
+    pub fn add_task(&mut self, name: String) -> Result<(InfoSubType, String), String> {
+        self.edit_displayed(|node| {
+            node.add_task(name.clone());
+            Ok(())
+        }, None)?;
+        Ok((InfoSubType::AddTask, name))
+    }
+
+    pub fn rename_task(&mut self, idx: usize, name: String) -> Result<(InfoSubType, String), String> {
+        self.edit_displayed(|node| {
+            let task = node.content.get_mut(idx).ok_or("The task is no longer in the file")?;
+            *task = name.clone();
+            Ok(())
+        }, None)?;
+        Ok((InfoSubType::RenameTask, name))
+    }
+
+    pub fn delete_task(&mut self, idx: usize) -> Result<(InfoSubType, String), String> {
+        let mut name = String::new();
+        self.edit_displayed(|node| {
+            if idx >= node.content.len() {
+                return Err("The task is no longer in the file".to_string());
+            }
+            name = node.remove_task(idx).name;
+            Ok(())
+        }, None)?;
+        Ok((InfoSubType::DeleteTask, name))
+    }
+
+    pub fn add_subheading(&mut self, name: String) -> Result<(InfoSubType, String), String> {
+        self.edit_displayed(|node| {
+            node.add_subheading(&name);
+            Ok(())
+        }, None)?;
+        Ok((InfoSubType::AddSubheading, name))
+    }
+
+    pub fn rename_subheading(&mut self, child_idx: usize, name: String) -> Result<(InfoSubType, String), String> {
+        self.edit_displayed(|node| {
+            let child = node.children.get_mut(child_idx).ok_or("The heading is no longer in the file")?;
+            child.rename_heading(&name)
+        }, None)?;
+        Ok((InfoSubType::RenameSubheading, name))
+    }
+
+    pub fn set_task_estimate(&mut self, idx: usize, text: String) -> Result<(InfoSubType, String), String> {
+        let estimate = parse_estimate(&text)?;
+        let mut name = String::new();
+        self.edit_displayed(|node| {
+            let task = node.content.get_mut(idx).ok_or("The task is no longer in the file")?;
+            *task = Node::with_estimate(task, estimate);
+            name = task.clone();
+            Ok(())
+        }, None)?;
+        Ok((InfoSubType::SetEstimate, name))
+    }
+
+    pub fn set_subheading_estimate(&mut self, child_idx: usize, text: String) -> Result<(InfoSubType, String), String> {
+        let estimate = parse_estimate(&text)?;
+        let mut name = String::new();
+        self.edit_displayed(|node| {
+            let child = node.children.get_mut(child_idx).ok_or("The heading is no longer in the file")?;
+            let heading = child.heading.as_deref().unwrap_or_default();
+            name = Node::with_estimate(heading.trim_start_matches(['#', '*']).trim(), estimate);
+            child.rename_heading(&name)
+        }, None)?;
+        Ok((InfoSubType::SetEstimate, name))
+    }
+
+    /// Adds to, subtracts from or replaces the time tracked on a task, from text like `+25m`,
+    /// `-1h` or `=2h30m`.
+    pub fn adjust_task_time(&mut self, idx: usize, text: String) -> Result<(InfoSubType, String), String> {
+        let text = text.trim();
+        let sign = text.chars().next().filter(|c| ['+', '-', '='].contains(c));
+        let (Some(sign), Some(amount)) = (sign, Node::parse_duration(text.get(1..).unwrap_or_default())) else {
+            return Err(format!("'{}' is not an adjustment like +25m, -1h or =2h30m", text));
+        };
+
+        let mut adjustment = None;
+        self.edit_displayed(|node| {
+            let Some(&previous) = node.content_times.get(idx) else {
+                return Err("The task is no longer in the file".to_string());
+            };
+            let adjusted = match sign {
+                '+' => previous.checked_add(amount),
+                '-' => Some(previous.saturating_sub(amount)),
+                _ => Some(amount),
+            };
+            let Some(adjusted) = adjusted else {
+                return Err(format!("'{}' is not an adjustment like +25m, -1h or =2h30m", text));
+            };
+            if adjusted > MAX_ADJUSTED_TIME {
+                return Err(format!("A task cannot hold more than {}", Node::format_short_duration(MAX_ADJUSTED_TIME)));
+            }
+            node.content_times[idx] = adjusted;
+
+            adjustment = Some((node.content[idx].clone(), previous, adjusted));
+            Ok(())
+        }, None)?;
+
+        let (name, previous, adjusted) = adjustment.unwrap_or_default();
+        self.adjustments.push(Adjustment {
+            heading_path: self.nav_bar.breadcrumbs.clone(),
+            task: Node::without_estimate(&name),
+            at: Local::now().timestamp(),
+            previous,
+            adjusted,
+        });
+
+        let info = format!("{} {} -> {}", name, format_duration(previous), format_duration(adjusted));
+        Ok((InfoSubType::AdjustTime, info))
+    }
+
+    /// Swaps the selected task or heading with its neighbour above or below, and follows it.
+    fn move_selected(&mut self, up: bool) -> Result<(InfoSubType, String), String> {
+        let idx = self.paginator.offset() + (self.selected_line as usize - 1);
+        let task_offset = self.tasks.task_offset;
+
+        let Some(target_idx) = (if up { idx.checked_sub(1) } else { Some(idx + 1) }) else {
+            return Ok((InfoSubType::None, "erm".to_string()));
+        };
+
+        // Tasks and headings are listed separately, so neither can move into the other's rows
+        let name = self.tasks.lines[idx].name.clone();
+        if idx < task_offset && target_idx < task_offset {
+            let (task_idx, target_task_idx) = (self.tasks.task_indices[idx], self.tasks.task_indices[target_idx]);
+            let swap = |node: &mut Node| {
+                node.swap_tasks(task_idx, target_task_idx);
+                Ok(())
+            };
+            self.edit_displayed(swap, Some(target_idx))?;
+        } else if idx >= task_offset && target_idx >= task_offset && target_idx < self.tasks.lines.len() {
+            let child_idx = self.tasks.subheading_indices[idx - task_offset];
+            let target_child_idx = self.tasks.subheading_indices[target_idx - task_offset];
+            let swap = |node: &mut Node| {
+                node.children.swap(child_idx, target_child_idx);
+                Ok(())
+            };
+            self.edit_displayed(swap, Some(target_idx))?;
+        } else {
+            return Ok((InfoSubType::None, "erm".to_string()));
+        }
+
+        Ok((InfoSubType::MoveEntry, name))
+    }
+
+    fn cut_task(&mut self) -> Result<(InfoSubType, String), String> {
+        let Some((idx, _)) = self.selected_task() else {
+            return Err("Only tasks can be cut".to_string());
+        };
+
+        if let Some(task) = &self.clipboard {
+            return Err(format!("Paste '{}' before cutting another task", task.name));
+        }
+
+        let mut cut = None;
+        self.edit_displayed(|node| {
+            cut = Some(node.remove_task(idx));
+            Ok(())
+        }, None)?;
+
+        let name = cut.as_ref().map(|task| task.name.clone()).unwrap_or_default();
+        self.clipboard = cut;
+        Ok((InfoSubType::CutTask, name))
+    }
+
+    fn paste_task(&mut self) -> Result<(InfoSubType, String), String> {
+        let Some(task) = self.clipboard.take() else {
+            return Err("No cut task to paste".to_string());
+        };
+
+        let name = task.name.clone();
+        let row_idx = self.tasks.task_offset;
+        let paste = |node: &mut Node| {
+            node.push_task(task.clone());
+            Ok(())
+        };
+        if let Err(e) = self.edit_displayed(paste, Some(row_idx)) {
+            self.clipboard = Some(task);
+            return Err(e);
+        }
+
+        Ok((InfoSubType::PasteTask, name))
+    }
+
+    /// Applies `edit` to the displayed heading in both the displayed node and the tree, then
+    /// selects the row at `select_idx`, or keeps the selected line when it is `None`.
+    fn edit_displayed(
+        &mut self,
+        edit: impl FnOnce(&mut Node) -> Result<(), String>,
+        select_idx: Option<usize>,
+    ) -> Result<(), String> {
+        self.update_time()?;
+        let node_path = self.update_root()?;
+
+        let mut edited = self.displayed_node.clone();
+        edit(&mut edited)?;
+
+        let Some(node) = self.root_node.get_node_mut(&node_path) else {
+            return Err("Node path not present on the given root node".to_string());
+        };
+
+        // The timer stays on its task through the edit, unless the edit took the task away
+        let running_row = self.tasks.active_time.map(|row| row as usize).filter(|&row| row < self.tasks.task_offset);
+        let resumed = running_row.and_then(|row| Node::follow_task(node, &edited, self.tasks.task_indices[row]));
+        let open_session = resumed.and_then(|_| self.open_session.take());
+        if let (Some(row), None) = (running_row, resumed) {
+            self.stopped_timer = Some(row + 1);
+        }
+
+        *node = edited;
+        self.root_node.refresh_total_time();
+
+        let row_idx = select_idx.unwrap_or(self.paginator.offset() + self.selected_line as usize - 1);
+        self.update_display_data(self.root_node.get_node(&node_path).unwrap().clone());
+        self.select_entry(row_idx);
+
+        let resumed_row = resumed.and_then(|task_idx| self.tasks.task_indices.iter().position(|idx| *idx == task_idx));
+        if let Some(row) = resumed_row {
+            self.tasks.active_time = Some(row as u16);
+            self.open_session = open_session.map(|(_, start)| (row, start));
+        }
+
+        Ok(())
+    }
+
+    /// Shows the page holding row `idx` and selects it, or the last row when `idx` is past it.
+    fn select_entry(&mut self, idx: usize) {
+        let idx = idx.min(self.tasks.lines.len().saturating_sub(1));
+
+        self.paginator.page = idx / self.paginator.page_size;
+        let (page_start, page_end) = self.paginator.page_slice();
+        self.tasks.slice_bounds(page_start, page_end);
+        self.content_height = self.paginator.content_height();
+
+        self.select_line((idx % self.paginator.page_size) as u16 + 1);
+    }
+
     fn update_paginator(&mut self, entry_len: usize) {
         self.paginator.entry_len = entry_len;
         let (page_start, page_end) = self.paginator.page_slice();
@@ -447,7 +1176,9 @@ impl TaskView {
         };
 
         let task_slice = self.tasks.task_slice();
-        self.displayed_node.completed_tasks = task_slice.iter().map(|e| e.completed).collect();
+        for (row, entry) in task_slice.iter().enumerate() {
+            self.displayed_node.completed_tasks[self.tasks.task_indices[row]] = entry.completed;
+        }
 
         if let Err(e) = self.root_node.update_node(&node_path, &self.displayed_node) {
             return Err(e);
@@ -509,6 +1240,38 @@ impl TaskView {
         return Ok((InfoSubType::EnterSubheading, heading_name));
     }
 
+    /// Displays the node at `target.path`, selecting `target.task` when given, and rebuilds
+    /// the breadcrumbs from the root.
+    pub fn jump_to(&mut self, target: &JumpTarget) -> Result<(InfoSubType, String), String> {
+        self.update_root()?;
+
+        let Some(new_node) = self.root_node.get_node(&target.path).cloned() else {
+            return Err("Jump target is no longer present in the task tree".to_string());
+        };
+
+        self.update_display_data(new_node);
+
+        self.nav_bar.clear_breadcrumbs();
+        for depth in 1..=target.path.len() {
+            let ancestor = self.root_node.get_node(&target.path[..depth].to_vec()).unwrap();
+            if let Some(heading) = ancestor.heading.clone() {
+                self.nav_bar.push_breadcrumb(heading);
+            }
+        }
+
+        let row = target.task.and_then(|task_idx| self.tasks.task_indices.iter().position(|idx| *idx == task_idx));
+        if let Some(row) = row {
+            self.select_entry(row);
+        }
+
+        let heading_name = self
+            .displayed_node
+            .heading
+            .clone()
+            .unwrap_or_else(|| "Root Node".to_string());
+        Ok((InfoSubType::JumpToTask, heading_name))
+    }
+
     fn add_breadcrumb(&mut self) {
         let new_heading_name = self.displayed_node.heading.clone();
 
@@ -541,9 +1304,14 @@ impl ViewEventHandler for TaskView {
         }
 
         return match key_code {
-            KeyCode::Char('s') => self.tasks.try_activate(),
+            KeyCode::Char('s') => self.toggle_timer(),
             KeyCode::Char(' ') => self.toggle_task(),
             KeyCode::Char('b') => self.enter_prev_node(),
+            KeyCode::Char('[') => self.move_selected(true),
+            KeyCode::Char(']') => self.move_selected(false),
+            KeyCode::Char('x') => self.cut_task(),
+            KeyCode::Char('p') => self.paste_task(),
+            KeyCode::Char('c') => self.toggle_hide_completed(),
             KeyCode::Enter => self.enter_next_node(),
             _ => Ok((InfoSubType::None, "erm".to_string())),
         };
@@ -553,10 +1321,22 @@ impl ViewEventHandler for TaskView {
 impl Widget for &TaskView {
     fn render(self, area: Rect, buf: &mut Buffer) {
         let vertical = Layout::vertical([Length(3), Min(0), Length(1)]);
-        let [navigation_row, task_area, page_area] = vertical.areas(area);
+        let [navigation_row, task_area, footer_area] = vertical.areas(area);
+
+        let horizontal = Layout::horizontal([Min(0), Length(40)]);
+        let [page_area, summary_area] = horizontal.areas(footer_area);
 
         self.nav_bar.render(navigation_row, buf);
         self.tasks.render(task_area, buf);
         self.paginator.render(page_area, buf);
+        self.render_summary(summary_area, buf);
+    }
+}
+
+impl TaskView {
+    fn render_summary(&self, area: Rect, buf: &mut Buffer) {
+        let total_time = self.displayed_node.rolled_up_time();
+        let summary = format!("Total {}", format_duration(total_time));
+        Line::from(summary).alignment(Alignment::Right).render(area, buf);
     }
 }
@@ -1,10 +1,13 @@
 pub mod window;
 pub use super::components::window::Window;
 
+mod backup_view;
 mod controls;
-mod log_view;
+pub mod log_view;
 mod main_view;
+mod outline_view;
 mod paginator;
 mod popups;
+mod task_list_view;
 
-use super::components::{controls::*, log_view::*, main_view::*, paginator::*, popups::*};
+use super::components::{backup_view::*, controls::*, log_view::*, main_view::*, outline_view::*, paginator::*, popups::*, task_list_view::*};
@@ -0,0 +1,244 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    prelude::{Buffer, Rect, Stylize},
+    style::{Color, Style},
+    text::Line,
+    widgets::Widget,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    diagnostic::JumpTarget,
+    info_subtype::InfoSubType,
+    node::{Node, NodePath},
+    reports::saved_state::heading_text,
+    traits::ViewEventHandler,
+};
+
+use super::{Task, Tasks};
+
+struct OutlineRow {
+    depth: usize,
+    task: Task,
+    target: JumpTarget,
+    /// Heading text from the root down to this heading, or `None` for a task row
+    heading_key: Option<Vec<String>>,
+    expanded: bool,
+}
+
+/// The whole tree as an indented outline, where each heading can be expanded in place.
+#[derive(Serialize, Deserialize, Default)]
+pub struct OutlineView {
+    /// Heading text paths of the headings that are expanded
+    pub expanded: Vec<Vec<String>>,
+
+    #[serde(skip)]
+    rows: Vec<OutlineRow>,
+    #[serde(skip)]
+    selected: usize,
+    #[serde(skip)]
+    selected_target: Option<JumpTarget>,
+
+    #[serde(skip)]
+    pending_timer: Option<JumpTarget>,
+    #[serde(skip)]
+    pending_jump: Option<JumpTarget>,
+}
+
+impl OutlineView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rebuilds the rows from `root`, keeping the same row selected when it is still shown.
+    pub fn refresh(&mut self, root: &Node, running: Option<&JumpTarget>) {
+        let mut rows = Vec::new();
+        self.collect_rows(root, 0, &mut Vec::new(), &mut NodePath::new(), &mut rows);
+
+        for row in rows.iter_mut() {
+            if Some(&row.target) == running {
+                row.task.style = Style::default().bold();
+                row.task.active = true;
+            }
+        }
+        self.rows = rows;
+
+        let previous = self
+            .selected_target
+            .as_ref()
+            .and_then(|target| self.rows.iter().position(|row| &row.target == target));
+        self.select(previous.unwrap_or(self.selected));
+    }
+
+    /// Takes the task whose timer the user asked to toggle, if any.
+    pub fn take_timer(&mut self) -> Option<JumpTarget> {
+        self.pending_timer.take()
+    }
+
+    /// Takes the heading or task the user asked to open, if any.
+    pub fn take_jump(&mut self) -> Option<JumpTarget> {
+        self.pending_jump.take()
+    }
+
+    fn collect_rows(
+        &self,
+        node: &Node,
+        depth: usize,
+        headings: &mut Vec<String>,
+        path: &mut NodePath,
+        rows: &mut Vec<OutlineRow>,
+    ) {
+        for (idx, name) in node.content.iter().enumerate() {
+            rows.push(OutlineRow {
+                depth,
+                task: Task {
+                    name: name.clone(),
+                    duration: node.content_times[idx],
+                    completed: node.completed_tasks[idx],
+                    progress: None,
+                    estimate: Node::estimate(name),
+                    active: false,
+                    metadata: node.task_metadata(idx),
+                    style: Style::default(),
+                },
+                target: JumpTarget { path: path.clone(), task: Some(idx) },
+                heading_key: None,
+                expanded: false,
+            });
+        }
+
+        for (child_idx, child) in node.children.iter().enumerate() {
+            if child.excluded {
+                continue;
+            }
+
+            headings.push(heading_text(child.heading.as_deref().unwrap_or_default()));
+            path.push(child_idx);
+
+            let expanded = self.expanded.contains(headings);
+            let mut task = Tasks::extract_entry(child);
+            task.name = heading_text(&task.name);
+
+            rows.push(OutlineRow {
+                depth,
+                task,
+                target: JumpTarget { path: path.clone(), task: None },
+                heading_key: Some(headings.clone()),
+                expanded,
+            });
+
+            if expanded {
+                self.collect_rows(child, depth + 1, headings, path, rows);
+            }
+
+            path.pop();
+            headings.pop();
+        }
+    }
+
+    fn select(&mut self, idx: usize) {
+        self.selected = idx.min(self.rows.len().saturating_sub(1));
+        self.selected_target = self.rows.get(self.selected).map(|row| row.target.clone());
+    }
+
+    fn expand_selected(&mut self) {
+        let Some(row) = self.rows.get(self.selected) else {
+            return;
+        };
+
+        if let Some(key) = row.heading_key.clone().filter(|_| !row.expanded) {
+            self.expanded.push(key);
+        }
+    }
+
+    /// Collapses the selected heading, or moves to the heading above a task or collapsed heading.
+    fn collapse_selected(&mut self) {
+        let Some(row) = self.rows.get(self.selected) else {
+            return;
+        };
+
+        if let Some(key) = row.heading_key.as_ref().filter(|_| row.expanded) {
+            self.expanded.retain(|expanded| expanded != key);
+            return;
+        }
+
+        let depth = row.depth;
+        if let Some(parent) = self.rows[..self.selected].iter().rposition(|row| row.depth + 1 == depth) {
+            self.select(parent);
+        }
+    }
+
+    fn request(&self) -> Result<JumpTarget, String> {
+        match self.rows.get(self.selected) {
+            Some(row) => Ok(row.target.clone()),
+            None => Err("Nothing on the selected line".to_string()),
+        }
+    }
+}
+
+impl ViewEventHandler for OutlineView {
+    fn handle_events(&mut self, key_code: KeyCode) -> Result<(InfoSubType, String), String> {
+        match key_code {
+            KeyCode::Char('j') => self.select(self.selected + 1),
+            KeyCode::Char('k') => self.select(self.selected.saturating_sub(1)),
+            KeyCode::Char('l') | KeyCode::Right => self.expand_selected(),
+            KeyCode::Char('h') | KeyCode::Left => self.collapse_selected(),
+            KeyCode::Char('s') => {
+                let Some(row) = self.rows.get(self.selected) else {
+                    return Err("Nothing on the selected line".to_string());
+                };
+                if row.heading_key.is_some() {
+                    return Err("Cannot start a subheading time".to_string());
+                }
+                if row.task.completed {
+                    return Err("Cannot start a time on a completed task".to_string());
+                }
+                self.pending_timer = Some(row.target.clone());
+            }
+            KeyCode::Enter => self.pending_jump = Some(self.request()?),
+            _ => (),
+        }
+
+        Ok((InfoSubType::None, "erm".to_string()))
+    }
+}
+
+impl Widget for &OutlineView {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if self.rows.is_empty() {
+            Line::from("No headings or tasks").fg(Color::DarkGray).render(area, buf);
+            return;
+        }
+
+        // Keep the selected row on screen
+        let visible_rows = area.height as usize;
+        let first_row = self.selected.saturating_sub(visible_rows.saturating_sub(1));
+
+        for (line, (idx, row)) in self.rows.iter().enumerate().skip(first_row).take(visible_rows).enumerate() {
+            let indent = row.depth as u16 * 2;
+            let marker = match (&row.heading_key, row.expanded) {
+                (None, _) => "  ",
+                (Some(_), false) => "▸ ",
+                (Some(_), true) => "▾ ",
+            };
+
+            let y = area.y + line as u16;
+            let marker_area = Rect::new(area.x + indent, y, 2, 1).intersection(area);
+            let task_area = Rect::new(area.x + indent + 2, y, area.width.saturating_sub(indent + 2), 1);
+
+            let mut task = row.task.clone();
+            if row.heading_key.is_some() {
+                task.style = task.style.fg(Color::Blue);
+            }
+            if task.completed {
+                task.style = task.style.fg(Color::DarkGray);
+            }
+            if idx == self.selected {
+                task.style = task.style.fg(Color::Black).bg(Color::Gray);
+            }
+
+            Line::from(marker).fg(Color::Blue).render(marker_area, buf);
+            task.render(task_area, buf);
+        }
+    }
+}
@@ -0,0 +1,28 @@
+/// Scores how well `query` matches `text` as a case-insensitive subsequence, or `None` when
+/// some query character is missing. Consecutive characters and matches at the start of a word
+/// score higher, and gaps between matches lower the score.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut text_idx = 0;
+    let mut last_match: Option<usize> = None;
+
+    for query_char in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = text[text_idx..].iter().position(|c| *c == query_char)? + text_idx;
+
+        let word_start = found == 0 || !text[found - 1].is_alphanumeric();
+        score += match last_match {
+            Some(last) if last + 1 == found => 15,
+            Some(last) => -((found - last - 1).min(10) as i64),
+            None => -(found.min(10) as i64),
+        };
+        if word_start {
+            score += 10;
+        }
+
+        last_match = Some(found);
+        text_idx = found + 1;
+    }
+
+    Some(score * 100 - text.len() as i64)
+}
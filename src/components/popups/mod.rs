@@ -1,4 +1,10 @@
-pub mod popup;
-pub use popup::*;
+pub mod modal;
+pub use modal::*;
+
+pub mod text_input;
+pub use text_input::*;
+
+pub mod fuzzy;
+pub use fuzzy::*;
 
 mod render;
@@ -0,0 +1,232 @@
+use crossterm::event::KeyCode;
+
+use super::{TextInput, fuzzy_score};
+
+/// What a modal was opened for, so its result can be handed back to the right caller.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ModalId {
+    Quit,
+    Writeback,
+    Error,
+    Export,
+    Search,
+    Archive,
+    AddTask,
+    RenameTask(usize),
+    DeleteTask(usize),
+    AddSubheading,
+    RenameSubheading(usize),
+    TaskEstimate(usize),
+    SubheadingEstimate(usize),
+    AdjustTime(usize),
+    ImportTimewarrior,
+    ReviewIntervals,
+    AssignInterval,
+}
+
+/// The answer a modal gives once it closes.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ModalResult {
+    Confirmed(bool),
+    Text(String),
+    Choice(usize),
+    Dismissed,
+    Cancelled,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum ModalKind {
+    /// A yes/no question whose message can be scrolled, shown as a diff when `diff` is set
+    Confirm { message: String, scroll: u16, diff: bool },
+    TextInput(TextInput),
+    Choice { options: Vec<String>, selected: usize },
+    /// A scrollable message that is closed with Enter or Esc
+    Message { message: String, scroll: u16, error: bool },
+    /// Text and detail of each entry, narrowed down by fuzzy matching the input as it is typed
+    Search {
+        input: TextInput,
+        entries: Vec<(String, String)>,
+        matches: Vec<usize>,
+        selected: usize,
+    },
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Modal {
+    pub id: ModalId,
+    pub title: String,
+    pub kind: ModalKind,
+}
+
+impl Modal {
+    pub fn confirm(id: ModalId, title: &str, message: &str) -> Self {
+        let kind = ModalKind::Confirm {
+            message: message.to_string(),
+            scroll: 0,
+            diff: false,
+        };
+        Modal::new(id, title, kind)
+    }
+
+    pub fn confirm_diff(id: ModalId, title: &str, diff: &str) -> Self {
+        let kind = ModalKind::Confirm {
+            message: diff.to_string(),
+            scroll: 0,
+            diff: true,
+        };
+        Modal::new(id, title, kind)
+    }
+
+    pub fn text_input(id: ModalId, title: &str, initial: &str) -> Self {
+        Modal::new(id, title, ModalKind::TextInput(TextInput::new(initial)))
+    }
+
+    pub fn choice(id: ModalId, title: &str, options: Vec<String>) -> Self {
+        Modal::new(id, title, ModalKind::Choice { options, selected: 0 })
+    }
+
+    /// Answers with `ModalResult::Choice` holding the index of the chosen entry.
+    pub fn search(id: ModalId, title: &str, entries: Vec<(String, String)>) -> Self {
+        let kind = ModalKind::Search {
+            input: TextInput::default(),
+            matches: (0..entries.len()).collect(),
+            entries,
+            selected: 0,
+        };
+        Modal::new(id, title, kind)
+    }
+
+    pub fn error(message: &str) -> Self {
+        let kind = ModalKind::Message {
+            message: message.to_string(),
+            scroll: 0,
+            error: true,
+        };
+        Modal::new(ModalId::Error, "Error", kind)
+    }
+
+    fn new(id: ModalId, title: &str, kind: ModalKind) -> Self {
+        Self {
+            id,
+            title: title.to_string(),
+            kind,
+        }
+    }
+
+    /// Handles a key press, returning the result when the key closes the modal.
+    fn handle_events(&mut self, key_code: KeyCode) -> Option<ModalResult> {
+        match &mut self.kind {
+            ModalKind::Confirm { scroll, .. } => match key_code {
+                KeyCode::Char('y') => Some(ModalResult::Confirmed(true)),
+                KeyCode::Char('n') | KeyCode::Esc => Some(ModalResult::Confirmed(false)),
+                _ => {
+                    Modal::scroll(key_code, scroll);
+                    None
+                }
+            },
+            ModalKind::TextInput(input) => match key_code {
+                KeyCode::Enter if input.value().trim().is_empty() => None,
+                KeyCode::Enter => Some(ModalResult::Text(input.value().trim().to_string())),
+                KeyCode::Esc => Some(ModalResult::Cancelled),
+                _ => {
+                    input.handle_key(key_code);
+                    None
+                }
+            },
+            ModalKind::Choice { options, selected } => match key_code {
+                KeyCode::Char('j') | KeyCode::Down => {
+                    *selected = (*selected + 1).min(options.len().saturating_sub(1));
+                    None
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    *selected = selected.saturating_sub(1);
+                    None
+                }
+                KeyCode::Enter => Some(ModalResult::Choice(*selected)),
+                KeyCode::Esc => Some(ModalResult::Cancelled),
+                _ => None,
+            },
+            ModalKind::Search {
+                input,
+                entries,
+                matches,
+                selected,
+            } => match key_code {
+                KeyCode::Down => {
+                    *selected = (*selected + 1).min(matches.len().saturating_sub(1));
+                    None
+                }
+                KeyCode::Up => {
+                    *selected = selected.saturating_sub(1);
+                    None
+                }
+                KeyCode::Enter => matches.get(*selected).map(|idx| ModalResult::Choice(*idx)),
+                KeyCode::Esc => Some(ModalResult::Cancelled),
+                _ => {
+                    input.handle_key(key_code);
+                    *matches = Modal::search_matches(input.value(), entries);
+                    *selected = 0;
+                    None
+                }
+            },
+            ModalKind::Message { scroll, .. } => match key_code {
+                KeyCode::Enter | KeyCode::Esc => Some(ModalResult::Dismissed),
+                _ => {
+                    Modal::scroll(key_code, scroll);
+                    None
+                }
+            },
+        }
+    }
+
+    /// Indices of the entries whose text matches `query`, best match first.
+    fn search_matches(query: &str, entries: &[(String, String)]) -> Vec<usize> {
+        let mut scored: Vec<(i64, usize)> = entries
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, (text, _))| fuzzy_score(query, text).map(|score| (score, idx)))
+            .collect();
+
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        scored.into_iter().map(|(_, idx)| idx).collect()
+    }
+
+    fn scroll(key_code: KeyCode, scroll: &mut u16) {
+        match key_code {
+            KeyCode::Char('j') | KeyCode::Down => *scroll = scroll.saturating_add(1),
+            KeyCode::Char('k') | KeyCode::Up => *scroll = scroll.saturating_sub(1),
+            _ => (),
+        }
+    }
+}
+
+/// Open modals, newest last. Only the newest one receives key presses, and each is drawn over
+/// the ones opened before it.
+#[derive(Default)]
+pub struct ModalStack {
+    modals: Vec<Modal>,
+}
+
+impl ModalStack {
+    pub fn push(&mut self, modal: Modal) {
+        self.modals.push(modal);
+    }
+
+    pub fn is_open(&self) -> bool {
+        !self.modals.is_empty()
+    }
+
+    pub fn modals(&self) -> &[Modal] {
+        &self.modals
+    }
+
+    /// Passes the key press to the newest modal, closing it and returning its result once
+    /// it has been answered.
+    pub fn handle_events(&mut self, key_code: KeyCode) -> Option<(ModalId, ModalResult)> {
+        let modal = self.modals.last_mut()?;
+        let result = modal.handle_events(key_code)?;
+
+        let modal = self.modals.pop().unwrap();
+        Some((modal.id, result))
+    }
+}
@@ -2,42 +2,203 @@ use ratatui::{
     layout::Flex,
     prelude::{
         Alignment, Buffer, Constraint,
-        Constraint::{Min, Percentage},
+        Constraint::{Length, Min, Percentage},
         Layout, Rect, Stylize,
     },
     style::Color,
-    text::Line,
-    widgets::{Block, Clear, Paragraph, Widget},
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph, Widget, Wrap},
 };
 
-use super::PopupType;
+use super::{Modal, ModalKind, ModalStack, TextInput};
 
-impl Widget for PopupType {
+impl Widget for &ModalStack {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let prompt_area = center(area, Percentage(20), Percentage(15));
+        for modal in self.modals() {
+            modal.render(area, buf);
+        }
+    }
+}
+
+impl Widget for &Modal {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        match &self.kind {
+            ModalKind::Confirm { message, scroll, diff } => {
+                render_confirm(&self.title, message, *scroll, *diff, area, buf)
+            }
+            ModalKind::TextInput(input) => render_text_input(&self.title, input, area, buf),
+            ModalKind::Choice { options, selected } => render_choice(&self.title, options, *selected, area, buf),
+            ModalKind::Message { message, scroll, error } => {
+                render_message(&self.title, message, *scroll, *error, area, buf)
+            }
+            ModalKind::Search {
+                input,
+                entries,
+                matches,
+                selected,
+            } => render_search(&self.title, input, entries, matches, *selected, area, buf),
+        }
+    }
+}
 
-        Clear.render(prompt_area, buf);
+/// Draws the modal frame, sized to fit `content_height` lines up to most of the screen, and
+/// returns the area inside it.
+fn render_frame(title: &str, content_height: usize, wide: bool, border: Color, area: Rect, buf: &mut Buffer) -> Rect {
+    let width = if wide { Percentage(80) } else { Percentage(50) };
+    let height = (content_height as u16 + 2).min(area.height * 4 / 5);
+    let prompt_area = center(area, width, Length(height));
 
-        let block = Block::bordered();
-        let inner_area = block.inner(prompt_area);
-        block.render(prompt_area, buf);
+    Clear.render(prompt_area, buf);
 
-        let [message_area, options_area] =
-            Layout::vertical([Percentage(60), Percentage(40)]).areas(inner_area);
+    let block = Block::bordered().title(format!(" {} ", title)).border_style(border);
+    let inner_area = block.inner(prompt_area);
+    block.render(prompt_area, buf);
 
-        let centered_message_area = center(message_area, Percentage(50), Min(0));
+    inner_area
+}
 
-        let message: &str = match self {
-            PopupType::ConfirmQuit => "Quit?",
-            _ => "???",
-        };
+fn render_confirm(title: &str, message: &str, scroll: u16, diff: bool, area: Rect, buf: &mut Buffer) {
+    let line_count = message.lines().count();
+    let inner_area = render_frame(title, line_count + 2, diff, Color::Reset, area, buf);
+
+    let [message_area, _, options_area] = Layout::vertical([Min(0), Length(1), Length(1)]).areas(inner_area);
+
+    let lines: Vec<Line> = message
+        .lines()
+        .map(|line| {
+            if !diff {
+                return Line::from(line.to_string());
+            }
+
+            let color = if line.starts_with("+++") || line.starts_with("---") {
+                Color::Gray
+            } else if line.starts_with('+') {
+                Color::Green
+            } else if line.starts_with('-') {
+                Color::Red
+            } else if line.starts_with("@@") {
+                Color::Cyan
+            } else {
+                Color::Reset
+            };
+            Line::from(line.to_string()).fg(color)
+        })
+        .collect();
+
+    let alignment = if diff { Alignment::Left } else { Alignment::Center };
+    Paragraph::new(lines)
+        .alignment(alignment)
+        .scroll((scroll, 0))
+        .render(message_area, buf);
+
+    render_options(options_area, buf, vec![Line::from("Y"), Line::from("N")]);
+}
 
-        Paragraph::new(message.to_string())
-            .alignment(Alignment::Center)
-            .render(centered_message_area, buf);
+fn render_text_input(title: &str, input: &TextInput, area: Rect, buf: &mut Buffer) {
+    let inner_area = render_frame(title, 3, false, Color::Reset, area, buf);
+
+    let [input_area, _, options_area] = Layout::vertical([Length(1), Length(1), Length(1)]).areas(inner_area);
+
+    render_input_line(input, input_area, buf);
+
+    render_options(options_area, buf, vec![Line::from("↵ Confirm"), Line::from("Esc Cancel")]);
+}
+
+/// Draws the input with the cursor highlighted, scrolled sideways to keep the cursor in view.
+fn render_input_line(input: &TextInput, input_area: Rect, buf: &mut Buffer) {
+    let chars: Vec<char> = input.value().chars().collect();
+    let visible_width = input_area.width.saturating_sub(1) as usize;
+    let first_visible = input.cursor().saturating_sub(visible_width);
+
+    let before: String = chars[first_visible..input.cursor()].iter().collect();
+    let at_cursor: String = chars.get(input.cursor()).map_or(" ".to_string(), |c| c.to_string());
+    let after: String = chars.iter().skip(input.cursor() + 1).collect();
+
+    Line::from(vec![
+        Span::raw(before),
+        Span::raw(at_cursor).fg(Color::Black).bg(Color::Gray),
+        Span::raw(after),
+    ])
+    .render(input_area, buf);
+}
 
-        render_options(options_area, buf);
+fn render_choice(title: &str, options: &[String], selected: usize, area: Rect, buf: &mut Buffer) {
+    let inner_area = render_frame(title, options.len() + 2, false, Color::Reset, area, buf);
+
+    let [list_area, _, hint_area] = Layout::vertical([Min(0), Length(1), Length(1)]).areas(inner_area);
+
+    // Keep the selected option in view when the list is taller than the modal
+    let scroll = (selected + 1).saturating_sub(list_area.height as usize) as u16;
+
+    let lines: Vec<Line> = options
+        .iter()
+        .enumerate()
+        .map(|(idx, option)| {
+            let line = Line::from(format!(" {} ", option));
+            if idx == selected {
+                line.fg(Color::Black).bg(Color::Gray)
+            } else {
+                line
+            }
+        })
+        .collect();
+
+    Paragraph::new(lines).scroll((scroll, 0)).render(list_area, buf);
+    render_options(hint_area, buf, vec![Line::from("↵ Select"), Line::from("Esc Cancel")]);
+}
+
+fn render_search(
+    title: &str,
+    input: &TextInput,
+    entries: &[(String, String)],
+    matches: &[usize],
+    selected: usize,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    let inner_area = render_frame(title, area.height as usize, true, Color::Reset, area, buf);
+
+    let [input_area, _, list_area, count_area] =
+        Layout::vertical([Length(1), Length(1), Min(0), Length(1)]).areas(inner_area);
+
+    render_input_line(input, input_area, buf);
+
+    // Keep the selected match in view when there are more than fit
+    let scroll = (selected + 1).saturating_sub(list_area.height as usize);
+    let detail_width = list_area.width / 2;
+
+    for (row, entry_idx) in matches.iter().skip(scroll).take(list_area.height as usize).enumerate() {
+        let (text, detail) = &entries[*entry_idx];
+        let row_area = Rect::new(list_area.x, list_area.y + row as u16, list_area.width, 1);
+        let [text_area, detail_area] = Layout::horizontal([Min(0), Length(detail_width)]).areas(row_area);
+
+        let mut text_line = Line::from(format!(" {}", text));
+        if row + scroll == selected {
+            text_line = text_line.fg(Color::Black).bg(Color::Gray);
+        }
+        text_line.render(text_area, buf);
+        Line::from(detail.clone()).fg(Color::Blue).render(detail_area, buf);
     }
+
+    Line::from(format!("{}/{}  ↑↓ Select  ↵ Jump  Esc Cancel", matches.len(), entries.len()))
+        .fg(Color::DarkGray)
+        .render(count_area, buf);
+}
+
+fn render_message(title: &str, message: &str, scroll: u16, error: bool, area: Rect, buf: &mut Buffer) {
+    let border = if error { Color::Red } else { Color::Reset };
+    let line_count = message.lines().count();
+    let inner_area = render_frame(title, line_count + 2, false, border, area, buf);
+
+    let [message_area, _, options_area] = Layout::vertical([Min(0), Length(1), Length(1)]).areas(inner_area);
+
+    Paragraph::new(message.to_string())
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .scroll((scroll, 0))
+        .render(message_area, buf);
+
+    render_options(options_area, buf, vec![Line::from("OK")]);
 }
 
 fn center(area: Rect, horizontal: Constraint, vertical: Constraint) -> Rect {
@@ -46,19 +207,16 @@ fn center(area: Rect, horizontal: Constraint, vertical: Constraint) -> Rect {
     return area;
 }
 
-fn render_options(area: Rect, buf: &mut Buffer) {
-    use Constraint::{Min, Percentage};
-
-    let options = vec![Line::from("Y"), Line::from("N")];
+fn render_options(area: Rect, buf: &mut Buffer, options: Vec<Line>) {
     let options_area = center(area, Percentage(50), Min(0));
 
-    let option_width = 3;
+    let option_width = options.iter().map(|option| option.width()).max().unwrap_or(1) + 2;
     let spacing = 1;
     let total_options = options.len();
     let total_width = total_options * option_width + (total_options - 1) * spacing;
 
     let option_area_center_x = options_area.x + options_area.width / 2;
-    let starting_x = option_area_center_x - (total_width / 2) as u16;
+    let starting_x = option_area_center_x.saturating_sub((total_width / 2) as u16);
 
     let mut current_x = starting_x;
     for option in options {
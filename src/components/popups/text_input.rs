@@ -0,0 +1,56 @@
+use crossterm::event::KeyCode;
+
+/// A single line of editable text with a cursor, counted in characters.
+#[derive(Default, Clone, PartialEq, Debug)]
+pub struct TextInput {
+    value: String,
+    cursor: usize,
+}
+
+impl TextInput {
+    pub fn new(initial: &str) -> Self {
+        Self {
+            value: initial.to_string(),
+            cursor: initial.chars().count(),
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn handle_key(&mut self, key_code: KeyCode) {
+        let len = self.value.chars().count();
+
+        match key_code {
+            KeyCode::Char(c) => {
+                self.value.insert(self.byte_index(), c);
+                self.cursor += 1;
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.value.remove(self.byte_index());
+            }
+            KeyCode::Delete if self.cursor < len => {
+                self.value.remove(self.byte_index());
+            }
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(len),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = len,
+            _ => (),
+        }
+    }
+
+    fn byte_index(&self) -> usize {
+        self
+            .value
+            .char_indices()
+            .nth(self.cursor)
+            .map_or(self.value.len(), |(idx, _)| idx)
+    }
+}
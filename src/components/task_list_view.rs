@@ -0,0 +1,285 @@
+use chrono::NaiveDate;
+use crossterm::event::KeyCode;
+use ratatui::{
+    prelude::{
+        Buffer,
+        Constraint::{Length, Min},
+        Layout, Rect, Stylize,
+    },
+    style::{Color, Style},
+    text::Line,
+    widgets::Widget,
+};
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter};
+
+use crate::{
+    diagnostic::JumpTarget,
+    info_subtype::InfoSubType,
+    node::Node,
+    reports::saved_state::heading_text,
+    traits::ViewEventHandler,
+};
+
+use super::Task;
+
+#[derive(Serialize, Deserialize, EnumIter, Display, Default, Clone, Copy, PartialEq)]
+pub enum TaskSort {
+    #[default]
+    #[strum(to_string = "File order")]
+    FileOrder,
+    #[strum(to_string = "Time spent")]
+    Time,
+    #[strum(to_string = "Completion")]
+    Completion,
+    #[strum(to_string = "Due date")]
+    DueDate,
+    #[strum(to_string = "Name")]
+    Name,
+}
+
+#[derive(Serialize, Deserialize, EnumIter, Display, Default, Clone, Copy, PartialEq)]
+pub enum StatusFilter {
+    #[default]
+    #[strum(to_string = "All")]
+    All,
+    #[strum(to_string = "Open")]
+    Open,
+    #[strum(to_string = "Completed")]
+    Completed,
+}
+
+/// Steps to the variant after `current`, wrapping back to the first.
+fn cycle<T: IntoEnumIterator + PartialEq>(current: T) -> T {
+    let mut variants = T::iter().skip_while(|variant| *variant != current).skip(1);
+    variants.next().unwrap_or_else(|| T::iter().next().unwrap())
+}
+
+struct TaskRow {
+    task: Task,
+    heading_path: String,
+    target: JumpTarget,
+    due: Option<NaiveDate>,
+    tags: Vec<String>,
+}
+
+/// Every task in the tree as one flat list, with the headings above each one.
+#[derive(Serialize, Deserialize, Default)]
+pub struct TaskListView {
+    pub sort: TaskSort,
+    pub status: StatusFilter,
+    pub tag: Option<String>,
+
+    #[serde(skip)]
+    rows: Vec<TaskRow>,
+    /// Indices into `rows` that pass the filters, in sorted order
+    #[serde(skip)]
+    visible: Vec<usize>,
+    #[serde(skip)]
+    selected: usize,
+    #[serde(skip)]
+    selected_target: Option<JumpTarget>,
+
+    #[serde(skip)]
+    pending_timer: Option<JumpTarget>,
+    #[serde(skip)]
+    pending_jump: Option<JumpTarget>,
+}
+
+impl TaskListView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rebuilds the rows from `root`, keeping the same task selected when it is still listed.
+    pub fn refresh(&mut self, root: &Node, running: Option<&JumpTarget>) {
+        self.rows.clear();
+        collect_rows(root, &mut Vec::new(), &mut Vec::new(), &mut self.rows);
+
+        for row in self.rows.iter_mut() {
+            if Some(&row.target) == running {
+                row.task.style = Style::default().bold();
+                row.task.active = true;
+            }
+        }
+
+        self.apply_filters();
+    }
+
+    /// Takes the task whose timer the user asked to toggle, if any.
+    pub fn take_timer(&mut self) -> Option<JumpTarget> {
+        self.pending_timer.take()
+    }
+
+    /// Takes the task the user asked to open under its heading, if any.
+    pub fn take_jump(&mut self) -> Option<JumpTarget> {
+        self.pending_jump.take()
+    }
+
+    fn apply_filters(&mut self) {
+        let rows = &self.rows;
+        let mut visible: Vec<usize> = (0..rows.len())
+            .filter(|&idx| match self.status {
+                StatusFilter::All => true,
+                StatusFilter::Open => !rows[idx].task.completed,
+                StatusFilter::Completed => rows[idx].task.completed,
+            })
+            .filter(|&idx| self.tag.as_ref().is_none_or(|tag| rows[idx].tags.contains(tag)))
+            .collect();
+
+        // Stable sorts, so ties stay in file order
+        match self.sort {
+            TaskSort::FileOrder => (),
+            TaskSort::Time => visible.sort_by(|a, b| rows[*b].task.duration.cmp(&rows[*a].task.duration)),
+            TaskSort::Completion => visible.sort_by_key(|idx| rows[*idx].task.completed),
+            TaskSort::DueDate => visible.sort_by_key(|idx| (rows[*idx].due.is_none(), rows[*idx].due)),
+            TaskSort::Name => visible.sort_by_key(|idx| rows[*idx].task.name.to_lowercase()),
+        }
+        self.visible = visible;
+
+        let previous = self
+            .selected_target
+            .as_ref()
+            .and_then(|target| self.visible.iter().position(|idx| &self.rows[*idx].target == target));
+        self.select(previous.unwrap_or(self.selected));
+    }
+
+    fn select(&mut self, position: usize) {
+        self.selected = position.min(self.visible.len().saturating_sub(1));
+        self.selected_target = self.selected_row().map(|row| row.target.clone());
+    }
+
+    fn selected_row(&self) -> Option<&TaskRow> {
+        self.visible.get(self.selected).map(|idx| &self.rows[*idx])
+    }
+
+    /// Moves the tag filter on to the next tag used by any task, then back to no filter.
+    fn cycle_tag(&mut self) {
+        let mut tags: Vec<&String> = self.rows.iter().flat_map(|row| row.tags.iter()).collect();
+        tags.sort();
+        tags.dedup();
+
+        let next = match &self.tag {
+            Some(tag) => tags.iter().position(|candidate| *candidate == tag).and_then(|idx| tags.get(idx + 1)),
+            None => tags.first(),
+        };
+        self.tag = next.map(|tag| tag.to_string());
+    }
+
+    fn request(&self) -> Result<JumpTarget, String> {
+        match self.selected_row() {
+            Some(row) => Ok(row.target.clone()),
+            None => Err("No task on the selected line".to_string()),
+        }
+    }
+}
+
+fn collect_rows(node: &Node, headings: &mut Vec<String>, path: &mut Vec<usize>, rows: &mut Vec<TaskRow>) {
+    if node.excluded {
+        return;
+    }
+
+    for (idx, name) in node.content.iter().enumerate() {
+        let task = Task {
+            name: name.clone(),
+            duration: node.content_times[idx],
+            completed: node.completed_tasks[idx],
+            progress: None,
+            estimate: Node::estimate(name),
+            active: false,
+            metadata: node.task_metadata(idx),
+            style: Style::default(),
+        };
+
+        rows.push(TaskRow {
+            task,
+            heading_path: headings.join(" / "),
+            target: JumpTarget { path: path.clone(), task: Some(idx) },
+            due: node.task_due_date(idx),
+            tags: Node::tags(name),
+        });
+    }
+
+    for (child_idx, child) in node.children.iter().enumerate() {
+        headings.push(heading_text(child.heading.as_deref().unwrap_or_default()));
+        path.push(child_idx);
+        collect_rows(child, headings, path, rows);
+        path.pop();
+        headings.pop();
+    }
+}
+
+impl ViewEventHandler for TaskListView {
+    fn handle_events(&mut self, key_code: KeyCode) -> Result<(InfoSubType, String), String> {
+        match key_code {
+            KeyCode::Char('j') => self.select(self.selected + 1),
+            KeyCode::Char('k') => self.select(self.selected.saturating_sub(1)),
+            KeyCode::Char('o') => {
+                self.sort = cycle(self.sort);
+                self.apply_filters();
+            }
+            KeyCode::Char('f') => {
+                self.status = cycle(self.status);
+                self.apply_filters();
+            }
+            KeyCode::Char('t') => {
+                self.cycle_tag();
+                self.apply_filters();
+            }
+            KeyCode::Char('s') if self.selected_row().is_some_and(|row| row.task.completed) => {
+                return Err("Cannot start a time on a completed task".to_string());
+            }
+            KeyCode::Char('s') => self.pending_timer = Some(self.request()?),
+            KeyCode::Enter => self.pending_jump = Some(self.request()?),
+            _ => (),
+        }
+
+        Ok((InfoSubType::None, "erm".to_string()))
+    }
+}
+
+impl Widget for &TaskListView {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [header_area, _, list_area] = Layout::vertical([Length(1), Length(1), Min(0)]).areas(area);
+
+        let tag = self.tag.clone().unwrap_or_else(|| "Any".to_string());
+        Line::from(format!(
+            "All tasks ({}/{})   (o) Sort: {}   (f) Status: {}   (t) Tag: {}",
+            self.visible.len(),
+            self.rows.len(),
+            self.sort,
+            self.status,
+            tag
+        ))
+        .fg(Color::Blue)
+        .render(header_area, buf);
+
+        if self.visible.is_empty() {
+            Line::from("No tasks match").fg(Color::DarkGray).render(list_area, buf);
+            return;
+        }
+
+        // Keep the selected row on screen
+        let visible_rows = list_area.height as usize;
+        let first_row = self.selected.saturating_sub(visible_rows.saturating_sub(1));
+        let path_width = list_area.width / 3;
+
+        for (row, position) in (first_row..self.visible.len()).take(visible_rows).enumerate() {
+            let task_row = &self.rows[self.visible[position]];
+            let row_area = Rect::new(list_area.x, list_area.y + row as u16, list_area.width, 1);
+            let [task_area, path_area] = Layout::horizontal([Min(0), Length(path_width)]).areas(row_area);
+
+            let mut task = task_row.task.clone();
+            if task.completed {
+                task.style = task.style.fg(Color::DarkGray);
+            }
+            if position == self.selected {
+                task.style = task.style.fg(Color::Black).bg(Color::Gray);
+            }
+
+            task.render(task_area, buf);
+            Line::from(task_row.heading_path.clone()).fg(Color::Blue).render(path_area, buf);
+        }
+    }
+}
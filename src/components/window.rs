@@ -11,15 +11,26 @@ use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 use strum_macros::{Display, EnumIter};
 
+use std::path::PathBuf;
+
 use crate::{
     app::SessionState,
+    backups::BackupEntry,
+    diagnostic::{Diagnostic, JumpTarget},
     info_subtype::InfoSubType,
     log_type::LogType,
-    node::Node,
+    node::{DetachedTask, Node},
+    reports::{
+        ExportKind,
+        saved_state::SavedState,
+        timewarrior::{self, Interval},
+    },
     traits::{EventHandler, ViewEventHandler},
 };
 
-use super::{Controls, LogView, PopupType, TaskView};
+use super::{BackupView, Controls, LogView, Modal, ModalId, ModalResult, ModalStack, OutlineView, TaskListView, TaskView};
+
+const FIXED_LAYOUT_MESSAGE: &str = "Headings come from the tasks' +project and @context tags. Edit the tags to move tasks";
 
 #[derive(Serialize, Deserialize, EnumIter, Display, Clone, Copy, PartialEq)]
 enum SelectedTab {
@@ -29,6 +40,20 @@ enum SelectedTab {
     Tab2,
     #[strum(to_string = "(3) Controls")]
     Tab3,
+    #[strum(to_string = "(4) Backups")]
+    Tab4,
+}
+
+/// How the Main tab lists the tree.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+enum MainMode {
+    /// One heading at a time, entered and left like directories
+    #[default]
+    Headings,
+    /// Every task in one list
+    AllTasks,
+    /// The whole tree indented, with headings expanded in place
+    Outline,
 }
 
 impl SelectedTab {
@@ -58,12 +83,42 @@ pub struct Window {
     pub title: String,
 
     selected_tab: SelectedTab,
+    #[serde(default)]
+    main_mode: MainMode,
 
     task_view: TaskView,
+    #[serde(default)]
+    task_list: TaskListView,
+    #[serde(default)]
+    outline: OutlineView,
     logger: LogView,
     #[serde(skip)]
     controls: Controls,
-    popup: PopupType,
+    #[serde(skip)]
+    backup_view: BackupView,
+    #[serde(skip)]
+    modals: ModalStack,
+    #[serde(skip)]
+    writeback_decision: Option<bool>,
+    #[serde(skip)]
+    export_request: Option<ExportKind>,
+    #[serde(skip)]
+    archive_request: bool,
+    /// File or directory of Timewarrior data the user asked to import
+    #[serde(skip)]
+    import_request: Option<PathBuf>,
+    /// Imported Timewarrior intervals that matched no task, waiting to be added to one by hand
+    #[serde(default)]
+    unmatched_intervals: Vec<Interval>,
+    /// Index into `unmatched_intervals` of the interval a task is being picked for
+    #[serde(skip)]
+    reviewing_interval: Option<usize>,
+    /// Where each entry of the open search modal jumps to
+    #[serde(skip)]
+    search_targets: Vec<JumpTarget>,
+    /// Set when the task format lays out the headings itself, as todo.txt does
+    #[serde(skip)]
+    fixed_layout: bool,
 }
 
 impl Window {
@@ -72,34 +127,74 @@ impl Window {
             title: "???".to_string(),
 
             task_view: TaskView::new(),
+            task_list: TaskListView::new(),
+            outline: OutlineView::new(),
 
             controls: Controls::new(),
             logger: LogView::new(),
+            backup_view: BackupView::new(),
 
             selected_tab: SelectedTab::Tab1,
-            popup: PopupType::None,
+            main_mode: MainMode::default(),
+            modals: ModalStack::default(),
+            writeback_decision: None,
+            export_request: None,
+            archive_request: false,
+            import_request: None,
+            unmatched_intervals: Vec::new(),
+            reviewing_interval: None,
+            search_targets: Vec::new(),
+            fixed_layout: false,
         }
     }
 
+    /// Restores a saved window over the task file that was just parsed. The tree stays as the
+    /// file has it, with the time saved for each task carried over, and anything logged before
+    /// the save was loaded is kept after the saved logs.
     pub fn load(&mut self, window: Window) {
         self.title = window.title;
 
+        let file_tree = self.task_view.root_node.clone();
         self.task_view = TaskView::new_with(window.task_view);
+        self.task_view.replace_tree(file_tree);
+        self.task_list = window.task_list;
+        self.outline = window.outline;
+        self.unmatched_intervals = window.unmatched_intervals;
 
         self.controls = Controls::new();
+        let unsaved_logs = std::mem::take(&mut self.logger.logs);
         self.logger = window.logger;
+        for entry in unsaved_logs {
+            self.logger.push_entry(entry);
+        }
 
         self.selected_tab = window.selected_tab;
-        self.popup = window.popup;
+        self.main_mode = window.main_mode;
     }
 
     pub fn log(&mut self, message: &str, log_type: LogType) {
         self.logger.log(message, log_type);
     }
 
+    pub fn log_diagnostic(&mut self, diagnostic: &Diagnostic) {
+        self.logger
+            .log_with_target(&diagnostic.print(), LogType::Warning, Some(diagnostic.target.clone()));
+    }
+
     pub fn update(&mut self) {
+        if let Some(line) = self.task_view.take_stopped_timer() {
+            self.log(&InfoSubType::StopTimer.message(line), LogType::INFO(InfoSubType::StopTimer));
+        }
+
         self.task_view.update();
         self.logger.update();
+
+        let running = self.task_view.running_task();
+        match self.main_mode {
+            MainMode::Headings => (),
+            MainMode::AllTasks => self.task_list.refresh(&self.task_view.root_node, running.as_ref()),
+            MainMode::Outline => self.outline.refresh(&self.task_view.root_node, running.as_ref()),
+        }
     }
 
     pub fn update_tree(&mut self, new_root: Node) {
@@ -107,26 +202,301 @@ impl Window {
         self.task_view.update_display_data(new_root);
     }
 
+    pub fn reload_tree(&mut self, new_root: Node) {
+        self.task_view.replace_tree(new_root);
+    }
+
     pub fn update_time(&mut self) {
         if let Err(e) = self.task_view.update_time() {
             self.log(&e, LogType::ERROR);
         }
     }
 
+    pub fn show_error(&mut self, message: &str) {
+        self.modals.push(Modal::error(message));
+    }
+
+    pub fn confirm_writeback(&mut self, diff: String) {
+        let title = "Review changes to task file  (j/k) Scroll";
+        self.modals.push(Modal::confirm_diff(ModalId::Writeback, title, &diff));
+    }
+
+    /// Takes the answer to the last writeback prompt, if one has been given.
+    pub fn take_writeback_decision(&mut self) -> Option<bool> {
+        self.writeback_decision.take()
+    }
+
+    pub fn set_backup_dir(&mut self, backup_dir: PathBuf) {
+        self.backup_view.backup_dir = backup_dir;
+    }
+
+    pub fn refresh_backups(&mut self) {
+        self.backup_view.refresh();
+    }
+
+    pub fn take_restore(&mut self) -> Option<BackupEntry> {
+        self.backup_view.take_restore()
+    }
+
+    pub fn close_session(&mut self) {
+        self.task_view.close_session();
+    }
+
     pub fn extract_node(&self) -> Node {
         return self.task_view.root_node.clone();
     }
+
+    pub fn saved_state(&self) -> SavedState {
+        SavedState {
+            root_node: self.extract_node(),
+            sessions: self.task_view.sessions.clone(),
+            adjustments: self.task_view.adjustments.clone(),
+            completions: self.task_view.completions.clone(),
+        }
+    }
+
+    /// Imports the intervals that match a task and keeps the rest for review, opening the review
+    /// list when there are any. Returns how many were imported and how many need review.
+    pub fn import_intervals(&mut self, intervals: Vec<Interval>) -> Result<(usize, usize), String> {
+        let (imported, unmatched) = self.task_view.import_intervals(intervals)?;
+
+        let unmatched_count = unmatched.len();
+        for interval in unmatched {
+            if !self.unmatched_intervals.contains(&interval) {
+                self.unmatched_intervals.push(interval);
+            }
+        }
+
+        if unmatched_count > 0 {
+            self.open_review_modal();
+        }
+        Ok((imported, unmatched_count))
+    }
+
+    pub fn set_fixed_layout(&mut self, fixed_layout: bool) {
+        self.fixed_layout = fixed_layout;
+    }
+
+    /// Whether a modal is waiting for input. Task modals hold the index of the task they were
+    /// opened on, so the tree must not be reloaded underneath them.
+    pub fn has_open_modal(&self) -> bool {
+        self.modals.is_open()
+    }
+
+    pub fn take_import_request(&mut self) -> Option<PathBuf> {
+        self.import_request.take()
+    }
+
+    pub fn take_export_request(&mut self) -> Option<ExportKind> {
+        self.export_request.take()
+    }
+
+    /// Whether the user has confirmed archiving completed tasks since this was last called.
+    pub fn take_archive_request(&mut self) -> bool {
+        std::mem::take(&mut self.archive_request)
+    }
+
+    pub fn archive_completed(
+        &mut self,
+        into_section: bool,
+        write_archive: impl FnOnce(&[(Vec<String>, DetachedTask)]) -> Result<(), String>,
+    ) -> Result<Vec<(Vec<String>, DetachedTask)>, String> {
+        self.task_view.archive_completed(into_section, write_archive)
+    }
+}
+
+impl Window {
+    /// Keys that add headings or move tasks, which a fixed layout cannot keep.
+    fn is_layout_key(key_code: KeyCode) -> bool {
+        matches!(key_code, KeyCode::Char('A' | '[' | ']' | 'x' | 'p'))
+    }
+
+    /// Whether keys that edit the displayed heading apply, rather than another tab or mode.
+    fn editing_headings(&self) -> bool {
+        self.selected_tab == SelectedTab::Tab1 && self.main_mode == MainMode::Headings
+    }
+
+    /// Opens the modal for `id` about the task on the selected line.
+    fn open_task_modal(&mut self, id: fn(usize) -> ModalId) -> SessionState {
+        let Some((idx, name)) = self.task_view.selected_task() else {
+            self.log("No task on the selected line", LogType::ERROR);
+            return SessionState::Running;
+        };
+
+        let modal = match id(idx) {
+            ModalId::DeleteTask(_) => Modal::confirm(id(idx), "Delete task", &format!("Delete '{}'?", name)),
+            ModalId::AdjustTime(_) => Modal::text_input(id(idx), "Adjust time (+25m, -1h or =2h30m)", ""),
+            _ => Modal::text_input(id(idx), "Rename task", &name),
+        };
+        self.modals.push(modal);
+
+        SessionState::AwaitingPrompt
+    }
+
+    fn open_rename_modal(&mut self) -> SessionState {
+        let Some((child_idx, name)) = self.task_view.selected_subheading() else {
+            return self.open_task_modal(ModalId::RenameTask);
+        };
+
+        if self.fixed_layout {
+            self.log(FIXED_LAYOUT_MESSAGE, LogType::ERROR);
+            return SessionState::Running;
+        }
+
+        self.modals
+            .push(Modal::text_input(ModalId::RenameSubheading(child_idx), "Rename subheading", &name));
+        SessionState::AwaitingPrompt
+    }
+
+    fn open_estimate_modal(&mut self) -> SessionState {
+        let (id, text) = match self.task_view.selected_subheading() {
+            Some(_) if self.fixed_layout => {
+                self.log(FIXED_LAYOUT_MESSAGE, LogType::ERROR);
+                return SessionState::Running;
+            }
+            Some((child_idx, name)) => (ModalId::SubheadingEstimate(child_idx), name),
+            None => match self.task_view.selected_task() {
+                Some((idx, name)) => (ModalId::TaskEstimate(idx), name),
+                None => {
+                    self.log("Nothing on the selected line", LogType::ERROR);
+                    return SessionState::Running;
+                }
+            },
+        };
+
+        let current = Node::estimate(&text).map(Node::format_short_duration).unwrap_or_default();
+        self.modals.push(Modal::text_input(id, "Estimate (e.g. 2h30m, none to clear)", &current));
+        SessionState::AwaitingPrompt
+    }
+
+    /// Lists the Timewarrior intervals that matched no task, so one can be picked and added to a
+    /// task by hand.
+    fn open_review_modal(&mut self) -> SessionState {
+        if self.unmatched_intervals.is_empty() {
+            self.log("No Timewarrior intervals are waiting for review", LogType::INFO(InfoSubType::General));
+            return SessionState::Running;
+        }
+
+        let options = self.unmatched_intervals.iter().map(Interval::describe).collect();
+        let title = "Unmatched Timewarrior intervals";
+        self.modals.push(Modal::choice(ModalId::ReviewIntervals, title, options));
+        SessionState::AwaitingPrompt
+    }
+
+    fn open_search_modal(&mut self) -> SessionState {
+        let (entries, targets) = self
+            .task_view
+            .search_entries()
+            .into_iter()
+            .map(|(text, breadcrumbs, target)| ((text, breadcrumbs), target))
+            .unzip();
+
+        self.search_targets = targets;
+        self.modals.push(Modal::search(ModalId::Search, "Find task or heading", entries));
+        SessionState::AwaitingPrompt
+    }
+
+    /// Acts on the answer from a closed modal.
+    fn handle_modal_result(&mut self, id: ModalId, result: ModalResult) -> SessionState {
+        let res = match (id, result) {
+            (ModalId::Quit, ModalResult::Confirmed(true)) => return SessionState::Quitting,
+            (ModalId::Writeback, ModalResult::Confirmed(accepted)) => {
+                self.writeback_decision = Some(accepted);
+                return SessionState::Running;
+            }
+            (ModalId::Archive, ModalResult::Confirmed(accepted)) => {
+                self.archive_request = accepted;
+                return SessionState::Running;
+            }
+            (ModalId::Export, ModalResult::Choice(idx)) => {
+                self.export_request = ExportKind::iter().nth(idx);
+                return SessionState::Running;
+            }
+            (ModalId::ImportTimewarrior, ModalResult::Text(path)) => {
+                self.import_request = Some(PathBuf::from(path.trim()));
+                return SessionState::Running;
+            }
+            (ModalId::ReviewIntervals, ModalResult::Choice(idx)) => {
+                let Some(interval) = self.unmatched_intervals.get(idx) else {
+                    return SessionState::Running;
+                };
+
+                let title = format!("Add {} to task", interval.describe());
+                let (entries, targets) = self
+                    .task_view
+                    .search_entries()
+                    .into_iter()
+                    .filter(|(_, _, target)| target.task.is_some())
+                    .map(|(text, breadcrumbs, target)| ((text, breadcrumbs), target))
+                    .unzip();
+
+                self.reviewing_interval = Some(idx);
+                self.search_targets = targets;
+                self.modals.push(Modal::search(ModalId::AssignInterval, &title, entries));
+                return SessionState::AwaitingPrompt;
+            }
+            (ModalId::AssignInterval, ModalResult::Choice(idx)) => {
+                let reviewing = self.reviewing_interval.take();
+                let (Some(interval_idx), Some(target)) = (reviewing, self.search_targets.get(idx).cloned()) else {
+                    return SessionState::Running;
+                };
+
+                let interval = self.unmatched_intervals[interval_idx].clone();
+                let res = self.task_view.assign_interval(&interval, &target);
+                if res.is_ok() {
+                    self.unmatched_intervals.remove(interval_idx);
+                    self.main_mode = MainMode::Headings;
+                }
+                res
+            }
+            (ModalId::Search, ModalResult::Choice(idx)) => match self.search_targets.get(idx).cloned() {
+                Some(target) => {
+                    self.main_mode = MainMode::Headings;
+                    self.task_view.jump_to(&target)
+                }
+                None => return SessionState::Running,
+            },
+            (ModalId::AddTask, ModalResult::Text(name)) => self.task_view.add_task(name),
+            (ModalId::RenameTask(idx), ModalResult::Text(name)) => self.task_view.rename_task(idx, name),
+            (ModalId::DeleteTask(idx), ModalResult::Confirmed(true)) => self.task_view.delete_task(idx),
+            (ModalId::AddSubheading, ModalResult::Text(name)) => self.task_view.add_subheading(name),
+            (ModalId::RenameSubheading(child_idx), ModalResult::Text(name)) => {
+                self.task_view.rename_subheading(child_idx, name)
+            }
+            (ModalId::AdjustTime(idx), ModalResult::Text(text)) => self.task_view.adjust_task_time(idx, text),
+            (ModalId::TaskEstimate(idx), ModalResult::Text(text)) => self.task_view.set_task_estimate(idx, text),
+            (ModalId::SubheadingEstimate(child_idx), ModalResult::Text(text)) => {
+                self.task_view.set_subheading_estimate(child_idx, text)
+            }
+            _ => return SessionState::Running,
+        };
+
+        match res {
+            Ok((log_type, info)) => self.log(&log_type.message(info), LogType::INFO(log_type)),
+            Err(e) => self.log(&e, LogType::ERROR),
+        }
+
+        SessionState::Running
+    }
 }
 
 impl EventHandler for Window {
     fn handle_events(&mut self, key_code: KeyCode) -> SessionState {
         let new_state: SessionState;
 
-        if self.popup != PopupType::None {
-            new_state = self.popup.handle_events(key_code);
-            if new_state != SessionState::AwaitingPrompt {
-                self.popup = PopupType::None;
+        if self.modals.is_open() {
+            new_state = match self.modals.handle_events(key_code) {
+                Some((id, result)) => self.handle_modal_result(id, result),
+                None => SessionState::AwaitingPrompt,
+            };
+
+            // A nested modal that is still open keeps waiting for its answer
+            if self.modals.is_open() && new_state == SessionState::Running {
+                return SessionState::AwaitingPrompt;
             }
+        } else if self.fixed_layout && self.editing_headings() && Window::is_layout_key(key_code) {
+            self.log(FIXED_LAYOUT_MESSAGE, LogType::ERROR);
+            return SessionState::Running;
         } else {
             new_state = match key_code {
                 KeyCode::Char('1') => {
@@ -141,18 +511,77 @@ impl EventHandler for Window {
                     self.selected_tab = SelectedTab::Tab3;
                     SessionState::Running
                 }
+                KeyCode::Char('4') => {
+                    self.selected_tab = SelectedTab::Tab4;
+                    self.backup_view.refresh();
+                    SessionState::Running
+                }
+                KeyCode::Char('v') if self.selected_tab == SelectedTab::Tab1 => {
+                    self.main_mode = match self.main_mode {
+                        MainMode::Headings => MainMode::AllTasks,
+                        MainMode::AllTasks => MainMode::Outline,
+                        MainMode::Outline => MainMode::Headings,
+                    };
+                    SessionState::Running
+                }
+                KeyCode::Char('a') if self.editing_headings() => {
+                    self.modals.push(Modal::text_input(ModalId::AddTask, "New task", ""));
+                    SessionState::AwaitingPrompt
+                }
+                KeyCode::Char('A') if self.editing_headings() => {
+                    self.modals
+                        .push(Modal::text_input(ModalId::AddSubheading, "New subheading", ""));
+                    SessionState::AwaitingPrompt
+                }
+                KeyCode::Char('r') if self.editing_headings() => self.open_rename_modal(),
+                KeyCode::Char('d') if self.editing_headings() => {
+                    self.open_task_modal(ModalId::DeleteTask)
+                }
+                KeyCode::Char('E') if self.editing_headings() => self.open_estimate_modal(),
+                KeyCode::Char('T') if self.editing_headings() => self.open_task_modal(ModalId::AdjustTime),
+                KeyCode::Char('e') if self.selected_tab == SelectedTab::Tab1 => {
+                    let options = ExportKind::iter().map(|kind| kind.to_string()).collect();
+                    self.modals.push(Modal::choice(ModalId::Export, "Export", options));
+                    SessionState::AwaitingPrompt
+                }
+                KeyCode::Char('Z') if self.selected_tab == SelectedTab::Tab1 => {
+                    let message = "Move every completed task to the archive?";
+                    self.modals.push(Modal::confirm(ModalId::Archive, "Archive completed tasks", message));
+                    SessionState::AwaitingPrompt
+                }
+                KeyCode::Char('/') if self.selected_tab == SelectedTab::Tab1 => self.open_search_modal(),
+                KeyCode::Char('I') if self.selected_tab == SelectedTab::Tab1 => {
+                    let data_dir = timewarrior::default_data_dir().display().to_string();
+                    let title = "Import Timewarrior data (file or directory)";
+                    self.modals.push(Modal::text_input(ModalId::ImportTimewarrior, title, &data_dir));
+                    SessionState::AwaitingPrompt
+                }
+                KeyCode::Char('U') if self.selected_tab == SelectedTab::Tab1 => self.open_review_modal(),
                 KeyCode::Esc => {
-                    self.popup = PopupType::ConfirmQuit;
+                    self.modals.push(Modal::confirm(ModalId::Quit, "Quit", "Quit?"));
                     SessionState::AwaitingPrompt
                 }
                 _ => SessionState::Running,
             };
 
-            let res = match self.selected_tab {
-                SelectedTab::Tab1 => self.task_view.handle_events(key_code),
-                SelectedTab::Tab2 => self.logger.handle_events(key_code),
+            let mut res = match (self.selected_tab, self.main_mode) {
+                (SelectedTab::Tab1, MainMode::Headings) => self.task_view.handle_events(key_code),
+                (SelectedTab::Tab1, MainMode::AllTasks) => self.task_list.handle_events(key_code),
+                (SelectedTab::Tab1, MainMode::Outline) => self.outline.handle_events(key_code),
+                (SelectedTab::Tab2, _) => self.logger.handle_events(key_code),
+                (SelectedTab::Tab4, _) => self.backup_view.handle_events(key_code),
                 _ => Ok((InfoSubType::None, "erm".to_string())),
             };
+
+            if let Some(target) = self.task_list.take_timer().or_else(|| self.outline.take_timer()) {
+                res = self.task_view.toggle_timer_at(&target);
+            }
+            let jump = self.logger.take_jump();
+            if let Some(target) = jump.or_else(|| self.task_list.take_jump()).or_else(|| self.outline.take_jump()) {
+                self.selected_tab = SelectedTab::Tab1;
+                self.main_mode = MainMode::Headings;
+                res = self.task_view.jump_to(&target);
+            }
             match res {
                 Ok((log_type, info)) => {
                     if log_type != InfoSubType::None {
@@ -189,13 +618,16 @@ impl Widget for &Window {
         block.render(body_area, buf);
 
         match self.selected_tab {
-            SelectedTab::Tab1 => self.task_view.render(inner_area, buf),
+            SelectedTab::Tab1 => match self.main_mode {
+                MainMode::Headings => self.task_view.render(inner_area, buf),
+                MainMode::AllTasks => self.task_list.render(inner_area, buf),
+                MainMode::Outline => self.outline.render(inner_area, buf),
+            },
             SelectedTab::Tab2 => self.logger.render(inner_area, buf),
             SelectedTab::Tab3 => self.controls.render(inner_area, buf),
+            SelectedTab::Tab4 => self.backup_view.render(inner_area, buf),
         }
 
-        if self.popup != PopupType::None {
-            self.popup.render(area, buf);
-        }
+        self.modals.render(area, buf);
     }
 }
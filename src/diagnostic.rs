@@ -0,0 +1,60 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::node::NodePath;
+
+/// A location in the task tree that a log entry can jump to.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct JumpTarget {
+    pub path: NodePath,
+    pub task: Option<usize>,
+}
+
+/// A problem found while parsing a task file.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Diagnostic {
+    pub file: Option<PathBuf>,
+    pub line: usize,
+    pub message: String,
+    pub target: JumpTarget,
+}
+
+impl Diagnostic {
+    pub fn new(line: usize, message: &str, path: &[usize], task: Option<usize>) -> Self {
+        Self {
+            file: None,
+            line,
+            message: message.to_string(),
+            target: JumpTarget {
+                path: path.to_vec(),
+                task,
+            },
+        }
+    }
+
+    /// Re-anchors a diagnostic from an included file at the node it was mounted on.
+    pub fn mounted_at(mut self, mount_path: &[usize], file: PathBuf) -> Self {
+        let mut path = mount_path.to_vec();
+        path.extend(self.target.path);
+        self.target.path = path;
+
+        if self.file.is_none() {
+            self.file = Some(file);
+        }
+
+        self
+    }
+
+    pub fn print(&self) -> String {
+        match &self.file {
+            Some(file) => format!(
+                "{} line {}: {}",
+                file.file_name().unwrap_or_default().to_string_lossy(),
+                self.line,
+                self.message
+            ),
+            None => format!("Line {}: {}", self.line, self.message),
+        }
+    }
+}
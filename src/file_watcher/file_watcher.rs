@@ -7,7 +7,9 @@ use std::sync::mpsc::{Receiver, channel};
 pub struct FileWatcher {
     pub file_name: String,
     pub file_path: PathBuf,
-    _watcher: RecommendedWatcher,
+    pub included_paths: Vec<PathBuf>,
+    watcher: RecommendedWatcher,
+    watched_dirs: Vec<PathBuf>,
     recv: Receiver<notify::Result<Event>>,
 }
 
@@ -23,24 +25,53 @@ impl FileWatcher {
 
         Ok(Self {
             file_name: path.to_string(),
-            file_path,
-            _watcher: watcher,
+            file_path: file_path.clone(),
+            included_paths: Vec::new(),
+            watcher,
+            watched_dirs: file_path.parent().map(Path::to_path_buf).into_iter().collect(),
             recv,
         })
     }
 
-    pub fn poll_change(&mut self) -> Option<String> {
-        match self.recv.try_recv() {
-            Ok(Ok(event)) => {
-                if self.filter_notify_event(event) {
-                    Some(self.read_file())
-                } else {
-                    None
+    /// Replaces the set of linked files that trigger a reload of the main file.
+    pub fn watch_includes(&mut self, paths: Vec<PathBuf>) -> notify::Result<()> {
+        for path in paths.iter() {
+            let Some(dir) = path.parent() else {
+                continue;
+            };
+
+            if !self.watched_dirs.iter().any(|watched| watched == dir) {
+                self.watcher.watch(dir, RecursiveMode::NonRecursive)?;
+                self.watched_dirs.push(dir.to_path_buf());
+            }
+        }
+
+        self.included_paths = paths;
+
+        Ok(())
+    }
+
+    /// Drains the pending events. Returns the watched files they changed along with the
+    /// contents of the main file, or `None` when no watched file changed.
+    pub fn poll_change(&mut self) -> Option<(Vec<PathBuf>, String)> {
+        let mut changed_paths: Vec<PathBuf> = Vec::new();
+        while let Ok(result) = self.recv.try_recv() {
+            let Ok(event) = result else {
+                continue;
+            };
+
+            for path in self.filter_notify_event(event) {
+                if !changed_paths.contains(&path) {
+                    changed_paths.push(path);
                 }
             }
-            Ok(Err(_e)) => None,
-            Err(_) => None,
         }
+
+        if changed_paths.is_empty() {
+            return None;
+        }
+
+        Some((changed_paths, self.read_file()))
     }
 
     pub fn read_file(&self) -> String {
@@ -60,14 +91,17 @@ impl FileWatcher {
         return Some((file_path, parent_path));
     }
 
-    fn filter_notify_event(&self, ev: Event) -> bool {
+    /// The watched files an event modified.
+    fn filter_notify_event(&self, ev: Event) -> Vec<PathBuf> {
         // FIX: swap order. check file first, then modification type
         if let EventKind::Modify(ModifyKind::Data(_)) = ev.kind {
-            if ev.paths.iter().any(|path| path == &self.file_path) {
-                return true;
-            }
+            let is_watched = |path: &&PathBuf| {
+                *path == &self.file_path || self.included_paths.iter().any(|included| included == *path)
+            };
+
+            return ev.paths.iter().filter(is_watched).cloned().collect();
         }
 
-        return false;
+        Vec::new()
     }
 }
@@ -0,0 +1,59 @@
+use std::fs;
+use std::path::Path;
+
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter};
+
+use crate::markdown_serialiser::markdown_serialiser::MarkdownFormat;
+use crate::org_serialiser::OrgFormat;
+use crate::todotxt_serialiser::{self, TodoTxtFormat};
+use crate::traits::TaskFormat;
+
+#[derive(EnumIter, Display, Clone, Copy, PartialEq, Debug)]
+pub enum FormatType {
+    #[strum(to_string = "markdown")]
+    Markdown,
+    #[strum(to_string = "org")]
+    Org,
+    #[strum(to_string = "todotxt")]
+    TodoTxt,
+}
+
+impl FormatType {
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            FormatType::Markdown => &["md", "markdown"],
+            FormatType::Org => &["org"],
+            FormatType::TodoTxt => &["txt"],
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        FormatType::iter().find(|format| format.to_string() == name.to_lowercase())
+    }
+
+    pub fn from_extension(file_path: &Path) -> Option<Self> {
+        let extension = file_path.extension()?.to_str()?.to_lowercase();
+        FormatType::iter().find(|format| format.extensions().contains(&extension.as_str()))
+    }
+
+    /// `.txt` is too common to mean todo.txt by itself, so a file only counts as one when it is
+    /// named `todo.txt` or `done.txt` or its lines read as todo.txt.
+    pub fn reads_as_todotxt(file_path: &Path) -> bool {
+        let file_name = file_path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        if ["todo.txt", "done.txt"].contains(&file_name.to_lowercase().as_str()) {
+            return true;
+        }
+
+        let contents = fs::read_to_string(file_path).unwrap_or_default();
+        todotxt_serialiser::looks_like_todotxt(&contents)
+    }
+
+    pub fn format(&self, follow_links: bool) -> Box<dyn TaskFormat> {
+        match self {
+            FormatType::Markdown => Box::new(MarkdownFormat { follow_links }),
+            FormatType::Org => Box::new(OrgFormat),
+            FormatType::TodoTxt => Box::new(TodoTxtFormat),
+        }
+    }
+}
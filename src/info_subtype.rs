@@ -16,12 +16,40 @@ pub enum InfoSubType {
     CompleteTask,
     #[strum(to_string = "Uncomplete Task")]
     UncompleteTask,
+    #[strum(to_string = "Add Task")]
+    AddTask,
+    #[strum(to_string = "Rename Task")]
+    RenameTask,
+    #[strum(to_string = "Delete Task")]
+    DeleteTask,
+    #[strum(to_string = "Add Subheading")]
+    AddSubheading,
+    #[strum(to_string = "Rename Subheading")]
+    RenameSubheading,
+    #[strum(to_string = "Move Entry")]
+    MoveEntry,
+    #[strum(to_string = "Cut Task")]
+    CutTask,
+    #[strum(to_string = "Paste Task")]
+    PasteTask,
+    #[strum(to_string = "Set Estimate")]
+    SetEstimate,
+    #[strum(to_string = "Adjust Time")]
+    AdjustTime,
+    #[strum(to_string = "Assign Interval")]
+    AssignInterval,
+    #[strum(to_string = "Hide Completed")]
+    HideCompleted,
+    #[strum(to_string = "Archive Tasks")]
+    ArchiveTasks,
     #[strum(to_string = "Starting Timer")]
     StartTimer,
     #[strum(to_string = "Stopping Timer")]
     StopTimer,
     #[strum(to_string = "Saving State")]
     Save,
+    #[strum(to_string = "Jump To Task")]
+    JumpToTask,
     #[strum(to_string = "None")]
     None,
 }
@@ -34,9 +62,23 @@ impl InfoSubType {
             EnterParent => format!("Entering parent: {}", info.to_string()),
             CompleteTask => format!("Completing task: {}", info.to_string()),
             UncompleteTask => format!("Cancelling completion of task: {}", info.to_string()),
+            AddTask => format!("Adding task: {}", info.to_string()),
+            RenameTask => format!("Renaming task to: {}", info.to_string()),
+            DeleteTask => format!("Deleting task: {}", info.to_string()),
+            AddSubheading => format!("Adding subheading: {}", info.to_string()),
+            RenameSubheading => format!("Renaming subheading to: {}", info.to_string()),
+            MoveEntry => format!("Moving: {}", info.to_string()),
+            CutTask => format!("Cutting task: {}", info.to_string()),
+            PasteTask => format!("Pasting task: {}", info.to_string()),
+            SetEstimate => format!("Setting estimate: {}", info.to_string()),
+            AdjustTime => format!("Adjusting time on: {}", info.to_string()),
+            AssignInterval => format!("Adding Timewarrior interval to: {}", info.to_string()),
+            HideCompleted => format!("{} completed tasks", info.to_string()),
+            ArchiveTasks => format!("Archived completed tasks to: {}", info.to_string()),
             StartTimer => format!("Starting time on task at line: {}", info.to_string()),
             StopTimer => format!("Stopping timer on task at line: {}", info.to_string()),
             Save => "Successfully saved the project".to_string(),
+            JumpToTask => format!("Jumping to: {}", info.to_string()),
             _ => "erm".to_string(),
         }
         .to_string();
@@ -10,6 +10,8 @@ use crate::info_subtype::InfoSubType;
 pub enum LogType {
     #[strum(to_string = "INFO")]
     INFO(InfoSubType),
+    #[strum(to_string = "WARNING")]
+    Warning,
     #[strum(to_string = "ERROR")]
     ERROR,
 }
@@ -18,6 +20,7 @@ impl LogType {
     pub fn color(&self) -> Color {
         return match self {
             LogType::INFO(_) => Color::Blue,
+            LogType::Warning => Color::Yellow,
             LogType::ERROR => Color::Red,
         };
     }
@@ -1,24 +1,82 @@
+mod backups;
 mod file_watcher;
 mod markdown_serialiser;
+mod org_serialiser;
+mod todotxt_serialiser;
 
 mod app;
+mod archive;
 mod components;
+mod diagnostic;
+mod format_type;
 mod info_subtype;
 mod log_type;
 mod node;
+mod project_settings;
+mod reports;
+mod session;
 mod traits;
 
 use crate::app::App;
+use crate::format_type::FormatType;
+use crate::project_settings::WritebackMode;
+use crate::reports::{estimates, html, ical, timewarrior};
+use crate::reports::saved_state::{self, SavedState};
+use crate::reports::timesheet::{self, Grouping, OutputFormat, ReportOptions};
 
 use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use strum::IntoEnumIterator;
+
+const SUBCOMMANDS: [&str; 5] = ["report", "estimates", "html", "ics", "timew-export"];
 
 fn main() {
+    if let Some(command) = subcommand() {
+        let res = match command.as_str() {
+            "report" => run_report(),
+            "estimates" => run_estimates_report(),
+            "ics" => run_ical_export(),
+            "timew-export" => run_timewarrior_export(),
+            "html" => run_html_export(),
+            _ => Err(format!("Unknown command {}", command)),
+        };
+
+        if let Err(e) = res {
+            println!("{}", e);
+        }
+        return;
+    }
+
     if let Some(file_name) = extract_file_name() {
+        let format_type = match select_format(&file_name) {
+            Ok(format_type) => format_type,
+            Err(e) => {
+                println!("{}", e);
+                return;
+            }
+        };
+
         let mut s_manager = App::new();
+        s_manager.set_format(format_type.format(has_flag("--follow-links")));
+
+        if has_flag("--read-only") {
+            s_manager.set_writeback_override(WritebackMode::ReadOnly);
+        } else if has_flag("--confirm-writeback") {
+            s_manager.set_writeback_override(WritebackMode::Confirm);
+        }
 
         let res = s_manager.attach_file_watcher(&file_name);
         if let Ok(_) = res {
-            //s_manager.load();
+            s_manager.load();
+            if let Some(data_path) = flag_value("--import-timew")
+                && let Err(e) = s_manager.import_timewarrior(Path::new(&data_path))
+            {
+                println!("{}", e);
+                return;
+            }
             s_manager.run();
         } else if let Err(e) = res {
             println!("{}", e);
@@ -28,11 +86,159 @@ fn main() {
     }
 }
 
+fn positional_args() -> Vec<String> {
+    env::args().skip(1).filter(|arg| !arg.starts_with("--")).collect()
+}
+
+fn subcommand() -> Option<String> {
+    positional_args()
+        .first()
+        .filter(|arg| SUBCOMMANDS.contains(&arg.as_str()))
+        .cloned()
+}
+
 fn extract_file_name() -> Option<String> {
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        return None;
+    let skip = if subcommand().is_some() { 1 } else { 0 };
+    positional_args().get(skip).cloned()
+}
+
+fn has_flag(flag: &str) -> bool {
+    env::args().skip(1).any(|arg| arg == flag)
+}
+
+fn flag_value(flag: &str) -> Option<String> {
+    let prefix = format!("{flag}=");
+    env::args().skip(1).find_map(|arg| arg.strip_prefix(&prefix).map(str::to_string))
+}
+
+fn select_format(file_name: &str) -> Result<FormatType, String> {
+    if let Some(name) = flag_value("--format") {
+        return FormatType::from_name(&name).ok_or_else(|| format!("Unknown file format '{}'", name));
+    }
+
+    let file_path = Path::new(file_name);
+    match FormatType::from_extension(file_path) {
+        Some(FormatType::TodoTxt) if !FormatType::reads_as_todotxt(file_path) => Err(format!(
+            "{} does not read as todo.txt. Pass --format=todotxt or --format=markdown to open it",
+            file_name
+        )),
+        Some(format_type) => Ok(format_type),
+        None => Ok(FormatType::Markdown),
+    }
+}
+
+/// `report <file> [--from=YYYY-MM-DD] [--to=YYYY-MM-DD] [--group=day|week] [--output=table|csv|json]`
+fn run_report() -> Result<(), String> {
+    let Some(file_name) = extract_file_name() else {
+        return Err("Provide the file name after the report command".to_string());
+    };
+
+    let options = ReportOptions {
+        from: date_flag("--from")?,
+        to: date_flag("--to")?,
+        grouping: named_flag("--group", Grouping::iter())?.unwrap_or(Grouping::Day),
+        output: named_flag("--output", OutputFormat::iter())?.unwrap_or(OutputFormat::Table),
+    };
+
+    let state = SavedState::load(&file_name)?;
+    let rows = timesheet::build_rows(&state, &options);
+    print!("{}", timesheet::render(&rows, options.output));
+
+    Ok(())
+}
+
+/// `estimates <file> [--output=table|csv|json]`
+fn run_estimates_report() -> Result<(), String> {
+    let Some(file_name) = extract_file_name() else {
+        return Err("Provide the file name after the estimates command".to_string());
+    };
+
+    let output = named_flag("--output", OutputFormat::iter())?.unwrap_or(OutputFormat::Table);
+
+    let state = SavedState::load(&file_name)?;
+    let rows = estimates::build_rows(&state);
+    print!("{}", estimates::render(&rows, output));
+
+    Ok(())
+}
+
+/// `html <file> [--out=path]`
+fn run_html_export() -> Result<(), String> {
+    let Some(file_name) = extract_file_name() else {
+        return Err("Provide the file name after the html command".to_string());
+    };
+
+    let output_path = match flag_value("--out") {
+        Some(path) => PathBuf::from(path),
+        None => html::default_output_path(&saved_state::canonical_path(&file_name)?),
+    };
+
+    let state = SavedState::load(&file_name)?;
+    let report = html::render(&state, &state.project_name(&file_name));
+    if let Err(e) = fs::write(&output_path, report) {
+        return Err(format!("Could not write {}: {}", output_path.display(), e));
     }
 
-    return Some(args[1].clone());
+    println!("Exported report to {}", output_path.display());
+    Ok(())
+}
+
+/// `ics <file> [--out=path]`
+fn run_ical_export() -> Result<(), String> {
+    let Some(file_name) = extract_file_name() else {
+        return Err("Provide the file name after the ics command".to_string());
+    };
+
+    let output_path = match flag_value("--out") {
+        Some(path) => PathBuf::from(path),
+        None => ical::default_output_path(&saved_state::canonical_path(&file_name)?),
+    };
+
+    let state = SavedState::load(&file_name)?;
+    let calendar = ical::render(&state.sessions, &state.project_name(&file_name));
+    if let Err(e) = fs::write(&output_path, calendar) {
+        return Err(format!("Could not write {}: {}", output_path.display(), e));
+    }
+
+    println!("Exported {} sessions to {}", state.sessions.len(), output_path.display());
+    Ok(())
+}
+
+/// `timew-export <file> [--out=dir]`
+fn run_timewarrior_export() -> Result<(), String> {
+    let Some(file_name) = extract_file_name() else {
+        return Err("Provide the file name after the timew-export command".to_string());
+    };
+
+    let data_dir = match flag_value("--out") {
+        Some(path) => PathBuf::from(path),
+        None => timewarrior::default_data_dir(),
+    };
+
+    let state = SavedState::load(&file_name)?;
+    let written = timewarrior::export(&state.sessions, &data_dir)?;
+
+    println!("Exported {} new intervals to {}", written, data_dir.display());
+    Ok(())
+}
+
+fn date_flag(flag: &str) -> Result<Option<NaiveDate>, String> {
+    let Some(value) = flag_value(flag) else {
+        return Ok(None);
+    };
+
+    NaiveDate::parse_from_str(&value, "%Y-%m-%d")
+        .map(Some)
+        .map_err(|_| format!("Expected a YYYY-MM-DD date for {}, got '{}'", flag, value))
+}
+
+fn named_flag<T: ToString>(flag: &str, mut options: impl Iterator<Item = T>) -> Result<Option<T>, String> {
+    let Some(value) = flag_value(flag) else {
+        return Ok(None);
+    };
+
+    options
+        .find(|option| option.to_string() == value)
+        .map(Some)
+        .ok_or_else(|| format!("Unknown value '{}' for {}", value, flag))
 }
@@ -1,22 +1,76 @@
-use std::fs;
+use std::path::{Path, PathBuf};
 
+use crate::diagnostic::Diagnostic;
 use crate::node::Node;
+use crate::project_settings::ProjectSettings;
+use crate::traits::TaskFormat;
 
-pub fn export(root_node: Node, file_name: String) {
-    let mut contents = String::new();
-    for child_node in root_node.children.iter() {
-        contents += &push_node(child_node, 0)
+pub struct MarkdownFormat {
+    pub follow_links: bool,
+}
+
+impl TaskFormat for MarkdownFormat {
+    fn parse(&self, buf: &str, file_path: &Path) -> (Node, Vec<Diagnostic>) {
+        if self.follow_links {
+            return Node::convert_from_linked(buf, file_path);
+        }
+
+        Node::convert_with_diagnostics(buf)
     }
 
-    fs::write(file_name, contents).expect("erm");
+    fn serialise(&self, root_node: &Node, file_path: &Path) -> Vec<(PathBuf, String)> {
+        export(root_node, file_path)
+    }
+
+    /// Each file is read back without following its links and compared with the part of the
+    /// tree it was written from.
+    fn verify(&self, root_node: &Node, files: &[(PathBuf, String)]) -> Result<(), (PathBuf, String)> {
+        for (idx, (output_path, contents)) in files.iter().enumerate() {
+            let expected = if idx == 0 {
+                root_node
+            } else {
+                match root_node.find_include(output_path) {
+                    Some(node) => node,
+                    None => return Err((output_path.clone(), "Included file is not in the tree".to_string())),
+                }
+            };
+
+            let parsed = Node::convert_from(contents);
+            if let Some(difference) = Node::first_difference(expected, &parsed) {
+                return Err((output_path.clone(), difference));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// With `annotate_headings` set in the front matter, each heading is followed by its rolled-up
+/// time and task counts, e.g. `## Backend <!-- 3h20m, 4/7 done -->`.
+pub fn export(root_node: &Node, file_path: &Path) -> Vec<(PathBuf, String)> {
+    let settings = ProjectSettings::from_front_matter(root_node.front_matter.as_deref()).unwrap_or_default();
+
+    let mut files: Vec<(PathBuf, String)> = Vec::new();
+    let contents = push_node(root_node, settings.annotate_headings, &mut files);
+    files.insert(0, (file_path.to_path_buf(), contents));
+
+    files
 }
 
-fn push_node(node: &Node, indent_len: usize) -> String {
+fn push_node(node: &Node, annotate: bool, files: &mut Vec<(PathBuf, String)>) -> String {
     let mut contents = String::new();
 
-    if node.heading.is_some() {
+    if let Some(front_matter) = &node.front_matter {
+        contents.push_str(&format!("---\n{}---\n", front_matter));
+    }
+
+    if node.heading.is_some() && node.include.is_none() {
         let heading = node.heading.clone().unwrap();
-        contents.push_str(&format!("{}\n", heading));
+        if annotate {
+            contents.push_str(&format!("{} {}\n", heading, annotation(node)));
+        } else {
+            contents.push_str(&format!("{}\n", heading));
+        }
     }
 
     for idx in 0..node.content.len() {
@@ -30,9 +84,56 @@ fn push_node(node: &Node, indent_len: usize) -> String {
         contents.push_str(&format!("{}{}\n", checkbox, node.content[idx]));
     }
 
-    for child_node in node.children.iter() {
-        contents += &push_node(child_node, indent_len + 1);
+    // Links are written before any subheading so they stay under the same heading on reload
+    for child_node in node.children.iter().filter(|child| child.include.is_some()) {
+        let include = child_node.include.clone().unwrap();
+        contents.push_str(&format!("{}\n", include.line));
+
+        let included_contents = push_node(child_node, annotate, files);
+        files.push((include.path, included_contents));
+    }
+    for line in node.repeated_links.iter() {
+        contents.push_str(&format!("{}\n", line));
+    }
+
+    for child_node in node.children.iter().filter(|child| child.include.is_none()) {
+        contents += &push_node(child_node, annotate, files);
     }
 
     return contents;
 }
+
+fn annotation(node: &Node) -> String {
+    let minutes = node.rolled_up_time().as_secs() / 60;
+    let time = match minutes / 60 {
+        0 => format!("{}m", minutes),
+        hours => format!("{}h{:02}m", hours, minutes % 60),
+    };
+
+    let (completed, total) = node.completion_counts();
+    format!("<!-- {}, {}/{} done -->", time, completed, total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TASKS: &str = "# Project\n- [ ] Write the parser\n- [x] Sketch the layout\n## Backend\n- [ ] Add the endpoint\n### Storage\n- [x] Pick a database\n## Frontend\n- [ ] Build the form\n";
+
+    #[test]
+    fn parse_serialise_parse_round_trips() {
+        let format = MarkdownFormat { follow_links: false };
+        let file_path = Path::new("tasks.md");
+
+        let (parsed, diagnostics) = format.parse(TASKS, file_path);
+        assert!(diagnostics.is_empty());
+
+        let files = format.serialise(&parsed, file_path);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].1, TASKS);
+
+        let (reparsed, _) = format.parse(&files[0].1, file_path);
+        assert_eq!(Node::first_difference(&parsed, &reparsed), None);
+        assert!(format.verify(&parsed, &files).is_ok());
+    }
+}
@@ -1,7 +1,12 @@
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
+use crate::diagnostic::Diagnostic;
+
 #[derive(Default, Clone, PartialEq, Debug, Deserialize, Serialize)]
 pub struct Node {
     pub heading: Option<String>,
@@ -11,10 +16,103 @@ pub struct Node {
     pub total_time: Duration,
     pub content_times: Vec<Duration>,
     pub completed_tasks: Vec<bool>,
+
+    #[serde(default)]
+    pub include: Option<Include>,
+    /// How each task was written in its source file. Formats with a single task syntax
+    /// leave this empty, so a missing entry means a checkbox.
+    #[serde(default)]
+    pub task_styles: Vec<TaskStyle>,
+    /// Fields each task carries beside its name. Left empty, like `task_styles`, until a
+    /// task has any.
+    #[serde(default)]
+    pub task_metadata: Vec<TaskMetadata>,
+
+    /// Raw text of the `---` block at the top of the file this node was parsed from
+    #[serde(default)]
+    pub front_matter: Option<String>,
+    #[serde(default)]
+    pub excluded: bool,
+    /// Link lines under this heading to files that are already mounted elsewhere in the tree.
+    /// They are written back as they are, but each file is only mounted once.
+    #[serde(default)]
+    pub repeated_links: Vec<String>,
+    /// Lines under the heading that are neither tasks nor headings, for formats that keep them
+    #[serde(default)]
+    pub heading_text: Vec<String>,
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Debug, Deserialize, Serialize)]
+pub enum TaskStyle {
+    #[default]
+    Checkbox,
+    Heading,
+}
+
+/// Parts of a task that are not its name: the `(A)` priority and `key:value` pairs of a
+/// todo.txt line, and the lines under an org task heading.
+#[derive(Default, Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct TaskMetadata {
+    pub priority: Option<char>,
+    pub fields: Vec<(String, String)>,
+    /// Planning lines such as `SCHEDULED:` and drawers other than the logbook
+    #[serde(default)]
+    pub drawers: Vec<String>,
+    /// Lines of the logbook, including its clock entries
+    #[serde(default)]
+    pub logbook: Vec<String>,
+    /// Any other text under the task
+    #[serde(default)]
+    pub body: Vec<String>,
+}
+
+impl TaskMetadata {
+    pub fn is_empty(&self) -> bool {
+        self.priority.is_none()
+            && self.fields.is_empty()
+            && self.drawers.is_empty()
+            && self.logbook.is_empty()
+            && self.body.is_empty()
+    }
+
+    pub fn field(&self, key: &str) -> Option<&str> {
+        self.fields.iter().find(|(field, _)| field == key).map(|(_, value)| value.as_str())
+    }
+}
+
+/// A task taken out of its heading along with its time, so it can be put under another one.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct DetachedTask {
+    pub name: String,
+    pub time: Duration,
+    pub completed: bool,
+    pub style: TaskStyle,
+    pub metadata: TaskMetadata,
+}
+
+/// A markdown link that has been mounted as a child node. The mounted node holds the
+/// linked file's root tasks as its content and the file's headings as its children.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct Include {
+    pub line: String,
+    pub path: PathBuf,
 }
 
 pub type NodePath = Vec<usize>;
 
+/// Text of the heading that archived tasks are moved under.
+pub const ARCHIVE_HEADING: &str = "Archive";
+
+#[derive(Default)]
+struct ParseState {
+    stack: Vec<usize>,
+    last_depth: Option<usize>,
+    fence_start: Option<usize>,
+
+    root_tasks: Vec<Diagnostic>,
+    diagnostics: Vec<Diagnostic>,
+}
+
 impl Node {
     pub fn new() -> Self {
         Self {
@@ -25,6 +123,15 @@ impl Node {
             total_time: Duration::default(),
             content_times: Vec::new(),
             completed_tasks: Vec::new(),
+
+            include: None,
+            task_styles: Vec::new(),
+            task_metadata: Vec::new(),
+
+            front_matter: None,
+            excluded: false,
+            repeated_links: Vec::new(),
+            heading_text: Vec::new(),
         }
     }
 
@@ -37,20 +144,664 @@ impl Node {
             total_time: Duration::default(),
             content_times: Vec::new(),
             completed_tasks: Vec::new(),
+
+            include: None,
+            task_styles: Vec::new(),
+            task_metadata: Vec::new(),
+
+            front_matter: None,
+            excluded: false,
+            repeated_links: Vec::new(),
+            heading_text: Vec::new(),
         }
     }
 
     pub fn convert_from(buf: &str) -> Self {
-        let lines: Vec<&str> = buf.split("\n").filter(|line| !line.is_empty()).collect();
+        Node::convert_with_diagnostics(buf).0
+    }
+
+    pub fn convert_with_diagnostics(buf: &str) -> (Self, Vec<Diagnostic>) {
+        Node::convert_inner(buf, None, &mut Vec::new())
+    }
+
+    /// Parses `buf` as the contents of `file_path`, mounting relative links to other
+    /// markdown files under the heading they appear in. Each file is mounted once, so links
+    /// back into a file being parsed or to a file linked from two places are not followed again.
+    pub fn convert_from_linked(buf: &str, file_path: &Path) -> (Self, Vec<Diagnostic>) {
+        let mut mounted: Vec<PathBuf> = Vec::new();
+        if let Ok(canonical) = file_path.canonicalize() {
+            mounted.push(canonical);
+        }
+
+        Node::convert_inner(buf, Some(file_path), &mut mounted)
+    }
+
+    fn convert_inner(
+        buf: &str,
+        file_path: Option<&Path>,
+        mounted: &mut Vec<PathBuf>,
+    ) -> (Self, Vec<Diagnostic>) {
+        let (front_matter, body) = Node::split_front_matter(buf);
+        let line_offset = buf[..buf.len() - body.len()].lines().count();
 
         let mut root = Node::new();
-        let mut stack: Vec<usize> = Vec::new();
+        root.front_matter = front_matter;
+        let mut state = ParseState::default();
+
+        for (idx, line) in body.split("\n").enumerate() {
+            let line_num = line_offset + idx + 1;
+            if line.is_empty() {
+                continue;
+            }
 
-        for line in lines {
-            Node::convert_line(line, &mut root, &mut stack);
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                state.fence_start = match state.fence_start {
+                    Some(_) => None,
+                    None => Some(line_num),
+                };
+                continue;
+            }
+
+            if state.fence_start.is_some() {
+                continue;
+            }
+
+            if let Some((text, path)) = file_path.and_then(|file_path| Node::link_target(line, file_path)) {
+                let current_node = Node::find_heading_level(&mut root, &state.stack);
+                if mounted.contains(&path) {
+                    current_node.repeated_links.push(line.to_string());
+                    continue;
+                }
+
+                if let Some((mounted_node, diagnostics)) = Node::mount_link(line, text, path, mounted) {
+                    let include_path = mounted_node.include.as_ref().unwrap().path.clone();
+                    current_node.children.push(mounted_node);
+
+                    let mut mount_path = state.stack.clone();
+                    mount_path.push(current_node.children.len() - 1);
+
+                    for diagnostic in diagnostics {
+                        state
+                            .diagnostics
+                            .push(diagnostic.mounted_at(&mount_path, include_path.clone()));
+                    }
+                    continue;
+                }
+            }
+
+            Node::convert_line(line, line_num, &mut root, &mut state);
+        }
+
+        if let Some(fence_line) = state.fence_start {
+            let diagnostic = Diagnostic::new(fence_line, "Code fence is never closed", &state.stack, None);
+            state.diagnostics.push(diagnostic);
+        }
+
+        if root.children.iter().any(|child| child.include.is_none()) {
+            state.diagnostics.append(&mut state.root_tasks);
+        }
+
+        (root, state.diagnostics)
+    }
+
+    /// Link text and canonical path of a relative link to a markdown file on `line`.
+    fn link_target(line: &str, file_path: &Path) -> Option<(String, PathBuf)> {
+        if line.starts_with('#') || line.starts_with("- [") {
+            return None;
+        }
+
+        let (text, target) = Node::extract_link(line)?;
+        let target = target.split('#').next().unwrap_or_default();
+
+        let is_remote = target.contains("://") || target.starts_with("mailto:");
+        if is_remote || target.starts_with('/') || !target.to_lowercase().ends_with(".md") {
+            return None;
         }
 
-        return root;
+        let base_dir = file_path.parent().unwrap_or(Path::new(""));
+        let path = base_dir.join(target).canonicalize().ok()?;
+        Some((text, path))
+    }
+
+    fn mount_link(line: &str, text: String, path: PathBuf, mounted: &mut Vec<PathBuf>) -> Option<(Node, Vec<Diagnostic>)> {
+        let contents = fs::read_to_string(&path).ok()?;
+
+        mounted.push(path.clone());
+        let (mut node, diagnostics) = Node::convert_inner(&contents, Some(&path), mounted);
+
+        node.heading = Some(text);
+        node.include = Some(Include {
+            line: line.to_string(),
+            path,
+        });
+
+        Some((node, diagnostics))
+    }
+
+    fn extract_link(line: &str) -> Option<(String, String)> {
+        let text_start = line.find('[')?;
+        let text_len = line[text_start..].find("](")?;
+        let text = &line[text_start + 1..text_start + text_len];
+
+        let target_start = text_start + text_len + 2;
+        let target_len = line[target_start..].find(')')?;
+        let target = &line[target_start..target_start + target_len];
+
+        if text.is_empty() || target.is_empty() {
+            return None;
+        }
+
+        Some((text.to_string(), target.trim().to_string()))
+    }
+
+    /// Separates a leading `---` block from the rest of the file. A block that is never
+    /// closed is treated as ordinary content.
+    pub fn split_front_matter(buf: &str) -> (Option<String>, &str) {
+        let Some(rest) = buf.strip_prefix("---\n").or_else(|| buf.strip_prefix("---\r\n")) else {
+            return (None, buf);
+        };
+
+        let mut offset = 0;
+        for line in rest.split_inclusive('\n') {
+            if line.trim_end() == "---" {
+                let front_matter = rest[..offset].to_string();
+                return (Some(front_matter), &rest[offset + line.len()..]);
+            }
+
+            offset += line.len();
+        }
+
+        (None, buf)
+    }
+
+    /// Flags every heading whose text matches one of `headings`, ignoring heading markers.
+    pub fn mark_excluded(&mut self, headings: &[String]) {
+        for child in self.children.iter_mut() {
+            if let Some(heading) = &child.heading {
+                let heading_text = heading.trim_start_matches(['#', '*']).trim();
+                child.excluded = headings.iter().any(|excluded| excluded.trim() == heading_text);
+            }
+
+            child.mark_excluded(headings);
+        }
+    }
+
+    /// Completed and total task counts for this node and every subheading that is not excluded.
+    /// Archived tasks are done with, so the archive heading is left out of the counts too.
+    pub fn completion_counts(&self) -> (usize, usize) {
+        let mut completed = self.completed_tasks.iter().filter(|done| **done).count();
+        let mut total = self.completed_tasks.len();
+
+        for child in self.children.iter().filter(|child| !child.excluded && !child.is_archive()) {
+            let (child_completed, child_total) = child.completion_counts();
+            completed += child_completed;
+            total += child_total;
+        }
+
+        (completed, total)
+    }
+
+    /// Recalculates `total_time` from the task times of this node and all of its subheadings.
+    pub fn refresh_total_time(&mut self) -> Duration {
+        let mut total: Duration = self.content_times.iter().sum();
+        for child in self.children.iter_mut() {
+            total += child.refresh_total_time();
+        }
+
+        self.total_time = total;
+        total
+    }
+
+    /// Every task with the heading text above it, as shown in the breadcrumbs, and where it is.
+    pub fn task_locations(&self) -> Vec<(Vec<String>, NodePath, usize)> {
+        fn collect(node: &Node, headings: &mut Vec<String>, path: &mut NodePath, out: &mut Vec<(Vec<String>, NodePath, usize)>) {
+            for idx in 0..node.content.len() {
+                out.push((headings.clone(), path.clone(), idx));
+            }
+
+            for (child_idx, child) in node.children.iter().enumerate() {
+                let heading = child.heading.as_deref().unwrap_or_default();
+                headings.push(Node::without_estimate(heading.trim_start_matches(['#', '*']).trim()));
+                path.push(child_idx);
+                collect(child, headings, path, out);
+                path.pop();
+                headings.pop();
+            }
+        }
+
+        let mut locations = Vec::new();
+        collect(self, &mut Vec::new(), &mut Vec::new(), &mut locations);
+        locations
+    }
+
+    /// Time spent on this node's tasks and on every subheading that is not excluded.
+    pub fn rolled_up_time(&self) -> Duration {
+        let mut total: Duration = self.content_times.iter().sum();
+        for child in self.children.iter().filter(|child| !child.excluded) {
+            total += child.rolled_up_time();
+        }
+
+        total
+    }
+
+    /// Describes the first heading, task or completion state that differs between the two
+    /// trees, or `None` when they match. Mounted files are compared on their own.
+    pub fn first_difference(expected: &Node, actual: &Node) -> Option<String> {
+        let location = expected.heading.clone().unwrap_or_else(|| "the root".to_string());
+
+        let task_count = std::cmp::max(expected.content.len(), actual.content.len());
+        for idx in 0..task_count {
+            let expected_task = expected.content.get(idx).map(|name| (name, expected.completed_tasks[idx]));
+            let actual_task = actual.content.get(idx).map(|name| (name, actual.completed_tasks[idx]));
+
+            if expected_task != actual_task {
+                return Some(format!(
+                    "Task {} under {}: expected {}, found {}",
+                    idx + 1,
+                    location,
+                    Node::describe_task(expected_task),
+                    Node::describe_task(actual_task)
+                ));
+            }
+        }
+
+        let expected_children: Vec<&Node> = expected.children.iter().filter(|c| c.include.is_none()).collect();
+        let actual_children: Vec<&Node> = actual.children.iter().filter(|c| c.include.is_none()).collect();
+
+        let child_count = std::cmp::max(expected_children.len(), actual_children.len());
+        for idx in 0..child_count {
+            let (Some(expected_child), Some(actual_child)) = (expected_children.get(idx), actual_children.get(idx))
+            else {
+                return Some(format!(
+                    "Under {}: expected {} subheadings, found {}",
+                    location,
+                    expected_children.len(),
+                    actual_children.len()
+                ));
+            };
+
+            if expected_child.heading != actual_child.heading {
+                return Some(format!(
+                    "Under {}: expected heading '{}', found '{}'",
+                    location,
+                    expected_child.heading.clone().unwrap_or_default(),
+                    actual_child.heading.clone().unwrap_or_default()
+                ));
+            }
+
+            if let Some(difference) = Node::first_difference(expected_child, actual_child) {
+                return Some(difference);
+            }
+        }
+
+        None
+    }
+
+    fn describe_task(task: Option<(&String, bool)>) -> String {
+        match task {
+            Some((name, true)) => format!("'[x] {}'", name),
+            Some((name, false)) => format!("'[ ] {}'", name),
+            None => "no task".to_string(),
+        }
+    }
+
+    pub fn find_include(&self, path: &Path) -> Option<&Node> {
+        if self.include.as_ref().is_some_and(|include| include.path == path) {
+            return Some(self);
+        }
+
+        self.children.iter().find_map(|child| child.find_include(path))
+    }
+
+    pub fn task_style(&self, idx: usize) -> TaskStyle {
+        self.task_styles.get(idx).copied().unwrap_or_default()
+    }
+
+    pub fn task_metadata(&self, idx: usize) -> TaskMetadata {
+        self.task_metadata.get(idx).cloned().unwrap_or_default()
+    }
+
+    pub fn task_metadata_mut(&mut self, idx: usize) -> &mut TaskMetadata {
+        self.task_metadata.resize(self.content.len(), TaskMetadata::default());
+        &mut self.task_metadata[idx]
+    }
+
+    /// Due date of a task, from its `due:` field or a `due:` word in its name.
+    pub fn task_due_date(&self, idx: usize) -> Option<NaiveDate> {
+        let due_field = self.task_metadata.get(idx).and_then(|metadata| metadata.field("due"));
+        match due_field {
+            Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d").ok(),
+            None => Node::due_date(&self.content[idx]),
+        }
+    }
+
+    /// The date in a `due:YYYY-MM-DD` word of a task name, as todo.txt writes it.
+    pub fn due_date(task_name: &str) -> Option<NaiveDate> {
+        task_name
+            .split_whitespace()
+            .find_map(|word| word.strip_prefix("due:"))
+            .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+    }
+
+    /// The time in an `est:2h30m` word of a task name or heading.
+    pub fn estimate(text: &str) -> Option<Duration> {
+        text
+            .split_whitespace()
+            .find_map(|word| word.strip_prefix("est:"))
+            .and_then(Node::parse_duration)
+    }
+
+    /// `text` with its `est:` word replaced by `estimate`, or removed when it is `None`. The
+    /// text is split on single spaces so the rest of it keeps its spacing.
+    pub fn with_estimate(text: &str, estimate: Option<Duration>) -> String {
+        let mut words: Vec<String> = text.split(' ').map(str::to_string).collect();
+        let position = words.iter().position(|word| word.starts_with("est:"));
+        words.retain(|word| !word.starts_with("est:"));
+
+        if let Some(estimate) = estimate {
+            let word = format!("est:{}", Node::format_short_duration(estimate));
+            words.insert(position.unwrap_or(words.len()), word);
+        }
+        words.join(" ")
+    }
+
+    /// `text` without its `est:` word. Tasks and headings are matched by this, so setting an
+    /// estimate does not make them a different task.
+    pub fn without_estimate(text: &str) -> String {
+        let words: Vec<&str> = text.split(' ').filter(|word| !word.starts_with("est:")).collect();
+        words.join(" ").trim_end().to_string()
+    }
+
+    /// Where task `idx` of `before` is in `after`, an edited copy of the same heading. The task
+    /// is found at the same index or by name, or at the same index under a new name when no
+    /// task was added or removed. Completed tasks are not followed.
+    pub fn follow_task(before: &Node, after: &Node, idx: usize) -> Option<usize> {
+        let name = Node::without_estimate(before.content.get(idx)?);
+        let same_name = |other: &String| Node::without_estimate(other) == name;
+
+        let followed = if after.content.get(idx).is_some_and(same_name) {
+            Some(idx)
+        } else if let Some(moved) = after.content.iter().position(same_name) {
+            Some(moved)
+        } else if after.content.len() == before.content.len() {
+            Some(idx)
+        } else {
+            None
+        };
+
+        followed.filter(|&idx| !after.completed_tasks[idx])
+    }
+
+    /// Estimated and actual time of the estimated work under this node: all of it when its
+    /// heading has an estimate, otherwise each estimated task and subheading below it.
+    pub fn estimate_totals(&self) -> Option<(Duration, Duration)> {
+        if let Some(estimate) = self.heading.as_deref().and_then(Node::estimate) {
+            return Some((estimate, self.rolled_up_time()));
+        }
+
+        let mut totals: Option<(Duration, Duration)> = None;
+        let mut add = |estimate: Duration, actual: Duration| {
+            let (total_estimate, total_actual) = totals.get_or_insert_default();
+            *total_estimate += estimate;
+            *total_actual += actual;
+        };
+
+        for (idx, name) in self.content.iter().enumerate() {
+            if let Some(estimate) = Node::estimate(name) {
+                add(estimate, self.content_times[idx]);
+            }
+        }
+        for child in self.children.iter().filter(|child| !child.excluded) {
+            if let Some((estimate, actual)) = child.estimate_totals() {
+                add(estimate, actual);
+            }
+        }
+
+        totals
+    }
+
+    /// Reads durations written as `2h30m`, `45m` or `90s`.
+    pub fn parse_duration(text: &str) -> Option<Duration> {
+        let mut total_secs = 0;
+        let mut number = String::new();
+        for c in text.chars() {
+            if c.is_ascii_digit() {
+                number.push(c);
+                continue;
+            }
+
+            let amount: u64 = number.parse().ok()?;
+            number.clear();
+
+            let secs = match c {
+                'h' => amount.checked_mul(3600)?,
+                'm' => amount.checked_mul(60)?,
+                's' => amount,
+                _ => return None,
+            };
+            total_secs = secs.checked_add(total_secs)?;
+        }
+
+        if !number.is_empty() || text.is_empty() {
+            return None;
+        }
+        Some(Duration::from_secs(total_secs))
+    }
+
+    /// Writes a duration the way `parse_duration` reads it, e.g. `2h30m`.
+    pub fn format_short_duration(duration: Duration) -> String {
+        let secs = duration.as_secs();
+        let mut formatted = String::new();
+        if secs >= 3600 {
+            formatted.push_str(&format!("{}h", secs / 3600));
+        }
+        if secs % 3600 >= 60 {
+            formatted.push_str(&format!("{}m", (secs % 3600) / 60));
+        }
+        if !secs.is_multiple_of(60) || formatted.is_empty() {
+            formatted.push_str(&format!("{}s", secs % 60));
+        }
+
+        formatted
+    }
+
+    /// Words of a task name tagged with `+`, `@` or `#`, such as todo.txt projects and contexts.
+    pub fn tags(task_name: &str) -> Vec<String> {
+        task_name
+            .split_whitespace()
+            .filter(|word| word.starts_with(['+', '@', '#']) && word.chars().nth(1).is_some_and(char::is_alphanumeric))
+            .map(|word| word.to_string())
+            .collect()
+    }
+
+    /// Appends an untimed, incomplete task after the existing ones.
+    pub fn add_task(&mut self, name: String) {
+        self.push_task(DetachedTask {
+            name,
+            time: Duration::default(),
+            completed: false,
+            style: TaskStyle::default(),
+            metadata: TaskMetadata::default(),
+        });
+    }
+
+    pub fn push_task(&mut self, task: DetachedTask) {
+        // Styles are only stored once a node has a task that is not a plain checkbox
+        if !self.task_styles.is_empty() || task.style != TaskStyle::default() {
+            self.task_styles.resize(self.content.len(), TaskStyle::default());
+            self.task_styles.push(task.style);
+        }
+        if !self.task_metadata.is_empty() || !task.metadata.is_empty() {
+            self.task_metadata.resize(self.content.len(), TaskMetadata::default());
+            self.task_metadata.push(task.metadata);
+        }
+
+        self.content.push(task.name);
+        self.content_times.push(task.time);
+        self.completed_tasks.push(task.completed);
+    }
+
+    pub fn remove_task(&mut self, idx: usize) -> DetachedTask {
+        let style = self.task_style(idx);
+        if idx < self.task_styles.len() {
+            self.task_styles.remove(idx);
+        }
+        let metadata = self.task_metadata(idx);
+        if idx < self.task_metadata.len() {
+            self.task_metadata.remove(idx);
+        }
+
+        DetachedTask {
+            name: self.content.remove(idx),
+            time: self.content_times.remove(idx),
+            completed: self.completed_tasks.remove(idx),
+            style,
+            metadata,
+        }
+    }
+
+    pub fn swap_tasks(&mut self, a: usize, b: usize) {
+        self.content.swap(a, b);
+        self.content_times.swap(a, b);
+        self.completed_tasks.swap(a, b);
+
+        if !self.task_styles.is_empty() {
+            self.task_styles.resize(self.content.len(), TaskStyle::default());
+            self.task_styles.swap(a, b);
+        }
+        if !self.task_metadata.is_empty() {
+            self.task_metadata.resize(self.content.len(), TaskMetadata::default());
+            self.task_metadata.swap(a, b);
+        }
+    }
+
+    /// Appends a subheading one level below this node, using the same heading marker.
+    pub fn add_subheading(&mut self, name: &str) {
+        let marker = self
+            .heading
+            .iter()
+            .chain(self.children.iter().filter_map(|child| child.heading.as_ref()))
+            .find_map(|heading| heading.chars().next().filter(|c| *c == '#' || *c == '*'))
+            .unwrap_or('#');
+
+        let depth = self.heading_depth() + 1;
+        let heading = format!("{} {}", marker.to_string().repeat(depth), name);
+        self.children.push(Node::new_with_heading(heading));
+    }
+
+    /// Replaces the text of the heading, keeping its level.
+    pub fn rename_heading(&mut self, name: &str) -> Result<(), String> {
+        if self.include.is_some() {
+            return Err("Headings mounted from a linked file are named by their link".to_string());
+        }
+
+        let Some(heading) = &self.heading else {
+            return Err("The root node has no heading to rename".to_string());
+        };
+
+        let marker: String = heading.chars().take_while(|c| *c == '#' || *c == '*').collect();
+        self.heading = Some(format!("{} {}", marker, name));
+        Ok(())
+    }
+
+    /// Removes every completed task outside excluded headings, included files and the archive
+    /// heading, and returns each with the heading text above it.
+    pub fn take_completed(&mut self) -> Vec<(Vec<String>, DetachedTask)> {
+        fn take(node: &mut Node, headings: &mut Vec<String>, out: &mut Vec<(Vec<String>, DetachedTask)>) {
+            let mut idx = 0;
+            while idx < node.content.len() {
+                if node.completed_tasks[idx] {
+                    out.push((headings.clone(), node.remove_task(idx)));
+                } else {
+                    idx += 1;
+                }
+            }
+
+            // Tasks from included files would otherwise be archived into this file
+            let archivable = |child: &&mut Node| !child.excluded && !child.is_archive() && child.include.is_none();
+            for child in node.children.iter_mut().filter(archivable) {
+                let heading = child.heading.as_deref().unwrap_or_default();
+                headings.push(Node::without_estimate(heading.trim_start_matches(['#', '*']).trim()));
+                take(child, headings, out);
+                headings.pop();
+            }
+        }
+
+        let mut taken = Vec::new();
+        take(self, &mut Vec::new(), &mut taken);
+        taken
+    }
+
+    /// The top level archive heading, added after the other headings at their level when the
+    /// tree has none yet. Fails when those headings have no level, as todo.txt projects do.
+    pub fn archive_heading(&mut self) -> Result<&mut Node, String> {
+        if let Some(idx) = self.children.iter().position(|child| child.is_archive()) {
+            return Ok(&mut self.children[idx]);
+        }
+
+        let (marker, depth) = match self.children.iter().find(|child| child.include.is_none()) {
+            Some(top_level) if top_level.heading_depth() == 0 => {
+                return Err("Headings in this file have no level to add an archive heading at".to_string());
+            }
+            Some(top_level) => (top_level.heading.as_ref().unwrap().chars().next().unwrap(), top_level.heading_depth()),
+            None => ('#', 2),
+        };
+
+        let heading = format!("{} {}", marker.to_string().repeat(depth), ARCHIVE_HEADING);
+        self.children.push(Node::new_with_heading(heading));
+        Ok(self.children.last_mut().unwrap())
+    }
+
+    fn is_archive(&self) -> bool {
+        let heading = self.heading.as_deref().unwrap_or_default();
+        self.include.is_none() && heading.trim_start_matches(['#', '*']).trim() == ARCHIVE_HEADING
+    }
+
+    fn heading_depth(&self) -> usize {
+        let Some(heading) = &self.heading else {
+            return 0;
+        };
+
+        heading.chars().take_while(|c| *c == '#' || *c == '*').count()
+    }
+
+    /// Copies the time of every task in `previous` to the task with the same name under the
+    /// same headings in this tree, so a reparsed file keeps the time tracked so far.
+    pub fn carry_times_from(&mut self, previous: &Node) {
+        let mut times: Vec<(Vec<String>, String, Duration)> = Vec::new();
+        for (headings, path, idx) in previous.task_locations() {
+            let node = previous.get_node(&path).unwrap();
+            times.push((headings, Node::without_estimate(&node.content[idx]), node.content_times[idx]));
+        }
+
+        for (headings, path, idx) in self.task_locations() {
+            let node = self.get_node_mut(&path).unwrap();
+            let name = Node::without_estimate(&node.content[idx]);
+            let found = times
+                .iter()
+                .position(|(prev_headings, prev_name, _)| *prev_headings == headings && *prev_name == name);
+
+            // Each previous task is used once, so duplicates keep their own times in order
+            if let Some(found_idx) = found {
+                node.content_times[idx] = times.remove(found_idx).2;
+            }
+        }
+
+        self.refresh_total_time();
+    }
+
+    pub fn included_files(&self) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        if let Some(include) = &self.include {
+            files.push(include.path.clone());
+        }
+
+        for child in self.children.iter() {
+            files.extend(child.included_files());
+        }
+
+        files
     }
 
     pub fn find_path(current: &Node, target: &Node) -> Result<NodePath, String> {
@@ -113,11 +864,12 @@ impl Node {
         return Ok(());
     }
 
-    fn convert_line(line: &str, root: &mut Node, indices: &mut Vec<usize>) {
-        let depth = line.chars().filter(|&c| c == '#').count();
+    fn convert_line(line: &str, line_num: usize, root: &mut Node, state: &mut ParseState) {
+        let depth = line.chars().take_while(|&c| c == '#').count();
+        let indices = &mut state.stack;
 
         if depth > 0 {
-            let heading = line.to_string();
+            let heading = Node::strip_annotation(line).to_string();
 
             while indices.len() >= depth {
                 indices.pop();
@@ -130,22 +882,83 @@ impl Node {
             if depth > indices.len() {
                 indices.push(parent_node.children.len() - 1);
             }
+
+            if let Some(last_depth) = state.last_depth
+                && depth > last_depth + 1
+            {
+                let message = format!("Heading jumps from level {} to level {}", last_depth, depth);
+                let diagnostic = Diagnostic::new(line_num, &message, indices, None);
+                state.diagnostics.push(diagnostic);
+            }
+            state.last_depth = Some(depth);
         } else {
             let prefixes = vec!["- [ ]", "- [x]"];
-            if let Some(content) = line.strip_prefix(prefixes[0]) {
-                let current_node = Node::find_heading_level(root, indices);
-                current_node.content.push(content.trim().to_string());
-                current_node.content_times.push(Duration::from_secs(0));
-                current_node.completed_tasks.push(false);
+
+            let task = if let Some(content) = line.strip_prefix(prefixes[0]) {
+                Some((content, false))
+            } else {
+                line.strip_prefix(prefixes[1]).map(|content| (content, true))
+            };
+
+            let Some((content, completed)) = task else {
+                if Node::is_checkbox_like(line) {
+                    let message = format!("Malformed checkbox is ignored: {}", line.trim());
+                    let diagnostic = Diagnostic::new(line_num, &message, indices, None);
+                    state.diagnostics.push(diagnostic);
+                }
+                return;
+            };
+
+            let name = content.trim().to_string();
+            let current_node = Node::find_heading_level(root, indices);
+            let task_idx = current_node.content.len();
+
+            if current_node.content.contains(&name) {
+                let message = format!("Duplicate task under the same heading: {}", name);
+                let diagnostic = Diagnostic::new(line_num, &message, indices, Some(task_idx));
+                state.diagnostics.push(diagnostic);
+            }
+
+            if indices.is_empty() {
+                let message = "Task appears before the first heading and is attached to the root";
+                let diagnostic = Diagnostic::new(line_num, message, indices, Some(task_idx));
+                state.root_tasks.push(diagnostic);
             }
 
-            if let Some(content) = line.strip_prefix(prefixes[1]) {
-                let current_node = Node::find_heading_level(root, indices);
-                current_node.content.push(content.trim().to_string());
-                current_node.content_times.push(Duration::from_secs(0));
-                current_node.completed_tasks.push(true);
+            current_node.content.push(name);
+            current_node.content_times.push(Duration::from_secs(0));
+            current_node.completed_tasks.push(completed);
+        }
+    }
+
+    /// Lines such as `-[ ]`, `* [x]`, `- [X]` or indented checkboxes, which look like tasks
+    /// but are not read as one.
+    fn is_checkbox_like(line: &str) -> bool {
+        let Some(item) = line.trim_start().strip_prefix(['-', '*', '+']) else {
+            return false;
+        };
+
+        let mut chars = item.trim_start().chars();
+        if chars.next() != Some('[') {
+            return false;
+        }
+
+        let marker: Vec<char> = chars.take(2).collect();
+        marker.first() == Some(&']') || marker.get(1) == Some(&']')
+    }
+
+    /// Removes a totals annotation such as `<!-- 3h20m, 4/7 done -->` written after a heading,
+    /// so annotated headings read back the same as plain ones.
+    fn strip_annotation(line: &str) -> &str {
+        let trimmed = line.trim_end();
+        if let Some(start) = trimmed.rfind("<!--") {
+            let comment = &trimmed[start..];
+            if comment.ends_with(" done -->") && comment.contains('/') {
+                return trimmed[..start].trim_end();
             }
         }
+
+        line
     }
 
     fn find_heading_level<'a>(root: &'a mut Node, indices: &[usize]) -> &'a mut Node {
@@ -156,3 +969,86 @@ impl Node {
         return node;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_duration_reads_hours_minutes_and_seconds() {
+        assert_eq!(Node::parse_duration("2h30m"), Some(Duration::from_secs(9000)));
+        assert_eq!(Node::parse_duration("45m"), Some(Duration::from_secs(2700)));
+        assert_eq!(Node::parse_duration("90s"), Some(Duration::from_secs(90)));
+        assert_eq!(Node::parse_duration("1h1s"), Some(Duration::from_secs(3601)));
+    }
+
+    #[test]
+    fn parse_duration_rejects_other_text() {
+        for text in ["", "30", "h", "2x", "1h 30m", "-5m", "99999999999999999999h", "5124095576030432h"] {
+            assert_eq!(Node::parse_duration(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn short_durations_read_back() {
+        for secs in [0, 59, 60, 3600, 9000, 9001] {
+            let duration = Duration::from_secs(secs);
+            assert_eq!(Node::parse_duration(&Node::format_short_duration(duration)), Some(duration));
+        }
+        assert_eq!(Node::format_short_duration(Duration::from_secs(9000)), "2h30m");
+        assert_eq!(Node::format_short_duration(Duration::ZERO), "0s");
+    }
+
+    #[test]
+    fn estimates_are_set_and_removed_in_place() {
+        let name = "Add the endpoint est:1h  +Backend";
+        assert_eq!(Node::estimate(name), Some(Duration::from_secs(3600)));
+        assert_eq!(Node::without_estimate(name), "Add the endpoint  +Backend");
+
+        let updated = Node::with_estimate(name, Some(Duration::from_secs(5400)));
+        assert_eq!(updated, "Add the endpoint est:1h30m  +Backend");
+        assert_eq!(Node::with_estimate("Add the endpoint", Some(Duration::from_secs(60))), "Add the endpoint est:1m");
+        assert_eq!(Node::with_estimate(name, None), "Add the endpoint  +Backend");
+    }
+
+    #[test]
+    fn estimate_totals_prefer_the_heading_estimate() {
+        let mut root = Node::convert_from("# Backend\n- [ ] Schema est:1h\n- [ ] Endpoint est:2h\n- [ ] Docs\n");
+        let backend = &mut root.children[0];
+        backend.content_times = vec![Duration::from_secs(1800), Duration::from_secs(3600), Duration::from_secs(600)];
+
+        // Docs has no estimate, so its time is left out
+        let estimated = (Duration::from_secs(3 * 3600), Duration::from_secs(5400));
+        assert_eq!(backend.estimate_totals(), Some(estimated));
+
+        backend.heading = Some("# Backend est:4h".to_string());
+        assert_eq!(backend.estimate_totals(), Some((Duration::from_secs(4 * 3600), Duration::from_secs(6000))));
+    }
+
+    #[test]
+    fn follow_task_finds_the_task_after_an_edit() {
+        let before = Node::convert_from("- [ ] Schema\n- [ ] Endpoint\n- [ ] Docs\n");
+
+        // Another task added above it
+        let mut added = before.clone();
+        added.content.insert(0, "Plan".to_string());
+        added.content_times.insert(0, Duration::ZERO);
+        added.completed_tasks.insert(0, false);
+        assert_eq!(Node::follow_task(&before, &added, 1), Some(2));
+
+        // Renamed, or given an estimate
+        let mut renamed = before.clone();
+        renamed.content[1] = "Users endpoint".to_string();
+        assert_eq!(Node::follow_task(&before, &renamed, 1), Some(1));
+        renamed.content[1] = "Endpoint est:2h".to_string();
+        assert_eq!(Node::follow_task(&before, &renamed, 1), Some(1));
+
+        // Deleted, or completed
+        let mut removed = before.clone();
+        removed.remove_task(1);
+        assert_eq!(Node::follow_task(&before, &removed, 1), None);
+        let mut completed = before.clone();
+        completed.completed_tasks[1] = true;
+        assert_eq!(Node::follow_task(&before, &completed, 1), None);
+    }
+}
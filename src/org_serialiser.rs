@@ -0,0 +1,466 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{Local, TimeDelta};
+
+use crate::diagnostic::Diagnostic;
+use crate::node::{Node, TaskStyle};
+use crate::traits::TaskFormat;
+
+const CLOCK_FORMAT: &str = "%Y-%m-%d %a %H:%M";
+
+/// Org-mode backend. `*` headings become nodes, `TODO`/`DONE` headings and `[ ]`/`[X]`
+/// checkboxes become tasks, and `CLOCK:` entries in a task's logbook become its time. Any
+/// other lines are kept in the tree with the task or heading they are under, so they are
+/// written back as they were.
+pub struct OrgFormat;
+
+/// Task keys are the heading path above a keyword task and its name.
+type TaskKey = (Vec<String>, String);
+
+impl TaskFormat for OrgFormat {
+    fn parse(&self, buf: &str, _file_path: &Path) -> (Node, Vec<Diagnostic>) {
+        parse(buf)
+    }
+
+    fn serialise(&self, root_node: &Node, file_path: &Path) -> Vec<(PathBuf, String)> {
+        // Earlier saves may have added clock entries that the tree has not read back yet
+        let existing = fs::read_to_string(file_path).unwrap_or_default();
+        let mut written_logbooks = HashMap::new();
+        collect_logbooks(&parse(&existing).0, &mut Vec::new(), &mut written_logbooks);
+
+        let mut heading_path = Vec::new();
+        let contents = push_node(root_node, &mut heading_path, &written_logbooks);
+
+        vec![(file_path.to_path_buf(), contents)]
+    }
+}
+
+fn parse(buf: &str) -> (Node, Vec<Diagnostic>) {
+    let mut root = Node::new();
+    let mut diagnostics = Vec::new();
+
+    let mut stack: Vec<usize> = Vec::new();
+    let mut levels: Vec<usize> = Vec::new();
+    let mut last_level: Option<usize> = None;
+    let mut current_task: Option<usize> = None;
+    // Name of the drawer the current task's lines are in
+    let mut drawer: Option<String> = None;
+
+    for (line_idx, line) in buf.lines().enumerate() {
+        let line_num = line_idx + 1;
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        if let Some(level) = heading_level(line) {
+            current_task = None;
+            drawer = None;
+
+            // Keyword headings close the headings at their level just as other headings do
+            while levels.last().is_some_and(|&parent_level| parent_level >= level) {
+                levels.pop();
+                stack.pop();
+            }
+
+            if let Some(last_level) = last_level
+                && level > last_level + 1
+            {
+                let message = format!("Heading jumps from level {} to level {}", last_level, level);
+                diagnostics.push(Diagnostic::new(line_num, &message, &stack, None));
+            }
+            last_level = Some(level);
+
+            let parent_node = find_heading_level(&mut root, &stack);
+            let title = line[level..].trim();
+            if let Some((completed, name)) = strip_keyword(title) {
+                push_task(parent_node, name, completed, TaskStyle::Heading);
+                current_task = Some(parent_node.content.len() - 1);
+                continue;
+            }
+
+            if ["TODO", "DONE"].contains(&title) {
+                let message = format!("{} heading has no task name and is read as a heading", title);
+                diagnostics.push(Diagnostic::new(line_num, &message, &stack, None));
+            }
+
+            parent_node.children.push(Node::new_with_heading(line.trim_end().to_string()));
+            stack.push(parent_node.children.len() - 1);
+            levels.push(level);
+            continue;
+        }
+
+        if let Some(message) = malformed_heading(line) {
+            diagnostics.push(Diagnostic::new(line_num, message, &stack, None));
+        }
+
+        if drawer.is_none()
+            && let Some((completed, name)) = strip_checkbox(line)
+        {
+            current_task = None;
+
+            let current_node = find_heading_level(&mut root, &stack);
+            push_task(current_node, name, completed, TaskStyle::Checkbox);
+            continue;
+        }
+
+        let current_node = find_heading_level(&mut root, &stack);
+        let Some(task_idx) = current_task else {
+            if trimmed.starts_with("CLOCK:") {
+                let message = "Clock entry outside a TODO heading is not counted";
+                diagnostics.push(Diagnostic::new(line_num, message, &stack, None));
+            }
+            current_node.heading_text.push(line.to_string());
+            continue;
+        };
+
+        if drawer.as_deref() == Some("LOGBOOK") || (drawer.is_none() && trimmed.starts_with("CLOCK:")) {
+            if trimmed == ":END:" {
+                drawer = None;
+                continue;
+            }
+
+            if let Some(clock) = trimmed.strip_prefix("CLOCK:") {
+                let tracked = clock_duration(clock).and_then(|duration| {
+                    current_node.content_times[task_idx]
+                        .checked_add(duration)
+                        .ok_or_else(|| "Clock entry is too long to add to the task and is not counted".to_string())
+                });
+                match tracked {
+                    Ok(tracked) => current_node.content_times[task_idx] = tracked,
+                    Err(message) => diagnostics.push(Diagnostic::new(line_num, &message, &stack, Some(task_idx))),
+                }
+            }
+            current_node.task_metadata_mut(task_idx).logbook.push(trimmed.to_string());
+        } else if drawer.is_none() && trimmed == ":LOGBOOK:" {
+            drawer = Some("LOGBOOK".to_string());
+        } else if drawer.is_some() || is_planning(trimmed) || drawer_name(trimmed).is_some() {
+            drawer = match drawer {
+                Some(_) if trimmed == ":END:" => None,
+                Some(name) => Some(name),
+                None => drawer_name(trimmed),
+            };
+            current_node.task_metadata_mut(task_idx).drawers.push(line.to_string());
+        } else {
+            current_node.task_metadata_mut(task_idx).body.push(line.to_string());
+        }
+    }
+
+    (root, diagnostics)
+}
+
+fn push_node(node: &Node, heading_path: &mut Vec<String>, written_logbooks: &HashMap<TaskKey, Vec<String>>) -> String {
+    let mut contents = String::new();
+
+    let level = match &node.heading {
+        Some(heading) => {
+            contents.push_str(&format!("{}\n", heading));
+            heading_path.push(heading.clone());
+            heading_level(heading).unwrap_or(0)
+        }
+        None => 0,
+    };
+
+    let push_lines = |contents: &mut String, lines: &[String]| {
+        for line in lines {
+            contents.push_str(&format!("{}\n", line));
+        }
+    };
+
+    push_lines(&mut contents, &node.heading_text);
+
+    // Tasks are written in file order, so checkboxes and keyword headings read back the same
+    for idx in 0..node.content.len() {
+        if node.task_style(idx) == TaskStyle::Checkbox {
+            let checkbox = if node.completed_tasks[idx] { "- [X]" } else { "- [ ]" };
+            contents.push_str(&format!("{} {}\n", checkbox, node.content[idx]));
+            continue;
+        }
+
+        let keyword = if node.completed_tasks[idx] { "DONE" } else { "TODO" };
+        let stars = "*".repeat(level + 1);
+        contents.push_str(&format!("{} {} {}\n", stars, keyword, node.content[idx]));
+
+        let metadata = node.task_metadata(idx);
+        push_lines(&mut contents, &metadata.drawers);
+
+        let key = (heading_path.clone(), node.content[idx].clone());
+        let written: Vec<String> = written_logbooks
+            .get(&key)
+            .into_iter()
+            .flatten()
+            .filter(|line| !metadata.logbook.contains(line))
+            .cloned()
+            .collect();
+        contents.push_str(&push_logbook(written, &metadata.logbook, node.content_times[idx]));
+
+        push_lines(&mut contents, &metadata.body);
+    }
+
+    for child_node in node.children.iter() {
+        contents += &push_node(child_node, heading_path, written_logbooks);
+    }
+
+    if node.heading.is_some() {
+        heading_path.pop();
+    }
+
+    contents
+}
+
+/// Logbook lines in the file for each keyword task, keyed by its heading path and name.
+fn collect_logbooks(node: &Node, heading_path: &mut Vec<String>, logbooks: &mut HashMap<TaskKey, Vec<String>>) {
+    if let Some(heading) = &node.heading {
+        heading_path.push(heading.clone());
+    }
+
+    for idx in 0..node.content.len() {
+        let key = (heading_path.clone(), node.content[idx].clone());
+        logbooks.entry(key).or_insert_with(|| node.task_metadata(idx).logbook);
+    }
+
+    for child_node in node.children.iter() {
+        collect_logbooks(child_node, heading_path, logbooks);
+    }
+
+    if node.heading.is_some() {
+        heading_path.pop();
+    }
+}
+
+/// Keeps the logbook lines read from the file, along with the clock entries earlier saves
+/// wrote since, and closes a new clock entry covering any time tracked after them.
+fn push_logbook(written: Vec<String>, logbook: &[String], tracked: Duration) -> String {
+    let mut clock_lines: Vec<String> = written.into_iter().chain(logbook.iter().cloned()).collect();
+    let clocked: Duration = clock_lines
+        .iter()
+        .filter_map(|line| line.strip_prefix("CLOCK:"))
+        .filter_map(|clock| clock_duration(clock).ok())
+        .fold(Duration::default(), Duration::saturating_add);
+
+    let untracked_minutes = tracked.saturating_sub(clocked).as_secs() / 60;
+    if untracked_minutes > 0 {
+        let end = Local::now();
+        let start = end - TimeDelta::minutes(untracked_minutes as i64);
+        let clock_line = format!(
+            "CLOCK: [{}]--[{}] => {:>2}:{:02}",
+            start.format(CLOCK_FORMAT),
+            end.format(CLOCK_FORMAT),
+            untracked_minutes / 60,
+            untracked_minutes % 60
+        );
+        clock_lines.insert(0, clock_line);
+    }
+
+    if clock_lines.is_empty() {
+        return String::new();
+    }
+
+    let mut logbook = ":LOGBOOK:\n".to_string();
+    for clock_line in clock_lines {
+        logbook.push_str(&format!("{}\n", clock_line));
+    }
+    logbook.push_str(":END:\n");
+
+    logbook
+}
+
+fn heading_level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|&c| c == '*').count();
+    if level > 0 && line[level..].starts_with(' ') {
+        return Some(level);
+    }
+
+    None
+}
+
+/// `SCHEDULED:`, `DEADLINE:` and `CLOSED:` lines, which belong right under their heading.
+fn is_planning(line: &str) -> bool {
+    ["SCHEDULED:", "DEADLINE:", "CLOSED:"].iter().any(|keyword| line.starts_with(keyword))
+}
+
+/// The name of the drawer a `:NAME:` line opens.
+fn drawer_name(line: &str) -> Option<String> {
+    let name = line.strip_prefix(':')?.strip_suffix(':')?;
+    if name.is_empty() || name == "END" || name.contains([' ', ':']) {
+        return None;
+    }
+
+    Some(name.to_string())
+}
+
+fn strip_keyword(title: &str) -> Option<(bool, String)> {
+    if let Some(name) = title.strip_prefix("TODO ") {
+        return Some((false, name.trim().to_string()));
+    }
+
+    if let Some(name) = title.strip_prefix("DONE ") {
+        return Some((true, name.trim().to_string()));
+    }
+
+    None
+}
+
+fn strip_checkbox(line: &str) -> Option<(bool, String)> {
+    let item = line.trim_start();
+    let item = item.strip_prefix("- ").or_else(|| item.strip_prefix("+ "))?;
+
+    if let Some(name) = item.strip_prefix("[ ]") {
+        return Some((false, name.trim().to_string()));
+    }
+
+    if let Some(name) = item.strip_prefix("[X]").or_else(|| item.strip_prefix("[x]")) {
+        return Some((true, name.trim().to_string()));
+    }
+
+    None
+}
+
+/// Reads the `=> H:MM` total of a closed clock entry. Running clocks have no total.
+fn clock_duration(clock: &str) -> Result<Duration, String> {
+    let Some((_, total)) = clock.split_once("=>") else {
+        return Err("Clock entry is still running and is not counted".to_string());
+    };
+
+    let parsed = total.trim().split_once(':').and_then(|(hours, minutes)| {
+        let hours: u64 = hours.trim().parse().ok()?;
+        let minutes: u64 = minutes.trim().parse().ok()?;
+        hours.checked_mul(3600)?.checked_add(minutes.checked_mul(60)?)
+    });
+
+    match parsed {
+        Some(secs) => Ok(Duration::from_secs(secs)),
+        None => Err(format!("Clock entry total '{}' is not a duration like 1:30 and is not counted", total.trim())),
+    }
+}
+
+/// Lines that look like a heading but are read as text, with the reason why.
+fn malformed_heading(line: &str) -> Option<&'static str> {
+    let stars = line.chars().take_while(|&c| c == '*').count();
+    if stars == 0 {
+        return None;
+    }
+
+    let title = &line[stars..];
+    if title.trim().is_empty() {
+        return Some("Heading has no title and is read as text");
+    }
+
+    if title.starts_with("TODO") || title.starts_with("DONE") {
+        return Some("Heading needs a space after its stars and is read as text");
+    }
+
+    None
+}
+
+fn push_task(node: &mut Node, name: String, completed: bool, style: TaskStyle) {
+    node.task_styles.resize(node.content.len(), TaskStyle::default());
+
+    node.content.push(name);
+    node.content_times.push(Duration::from_secs(0));
+    node.completed_tasks.push(completed);
+    node.task_styles.push(style);
+}
+
+fn find_heading_level<'a>(root: &'a mut Node, indices: &[usize]) -> &'a mut Node {
+    let mut node = root;
+    for &idx in indices {
+        node = &mut node.children[idx];
+    }
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TASKS: &str = "\
+#+TITLE: Project
+* Backend
+Notes about the backend
+- [ ] Sketch the schema
+- [X] Pick a database
+** TODO Add the endpoint
+SCHEDULED: <2026-10-20 Tue>
+:PROPERTIES:
+:EFFORT: 2:00
+:END:
+:LOGBOOK:
+CLOCK: [2026-10-18 Sun 09:00]--[2026-10-18 Sun 10:30] =>  1:30
+:END:
+Remember the rate limits
+** DONE Write the migration
+* Frontend
+- [ ] Build the form
+";
+
+    fn missing_file() -> PathBuf {
+        std::env::temp_dir().join("org-serialiser-test-missing").join("tasks.org")
+    }
+
+    #[test]
+    fn parse_serialise_parse_round_trips() {
+        let (parsed, diagnostics) = parse(TASKS);
+        assert!(diagnostics.is_empty());
+
+        let backend = &parsed.children[0];
+        let names = vec!["Sketch the schema", "Pick a database", "Add the endpoint", "Write the migration"];
+        assert_eq!(backend.content, names);
+        assert_eq!(backend.completed_tasks, vec![false, true, false, true]);
+        assert_eq!(backend.content_times[2], Duration::from_secs(90 * 60));
+
+        let files = OrgFormat.serialise(&parsed, &missing_file());
+        assert_eq!(files[0].1, TASKS);
+
+        let (reparsed, _) = parse(&files[0].1);
+        assert_eq!(Node::first_difference(&parsed, &reparsed), None);
+        assert_eq!(reparsed, parsed);
+    }
+
+    #[test]
+    fn renamed_task_keeps_its_lines() {
+        let (mut parsed, _) = parse(TASKS);
+        parsed.children[0].content[2] = "Add the users endpoint".to_string();
+
+        let files = OrgFormat.serialise(&parsed, &missing_file());
+        let (reparsed, _) = parse(&files[0].1);
+
+        let metadata = reparsed.children[0].task_metadata(2);
+        assert_eq!(metadata, parsed.children[0].task_metadata(2));
+        assert_eq!(reparsed.children[0].content_times[2], Duration::from_secs(90 * 60));
+    }
+
+    #[test]
+    fn tracked_time_is_written_as_one_new_clock_entry() {
+        let (mut parsed, _) = parse(TASKS);
+        parsed.children[0].content_times[2] += Duration::from_secs(20 * 60);
+
+        let files = OrgFormat.serialise(&parsed, &missing_file());
+        let clock_lines = files[0].1.lines().filter(|line| line.starts_with("CLOCK:")).count();
+        assert_eq!(clock_lines, 2);
+        assert!(files[0].1.contains("=>  0:20"));
+
+        let (reparsed, _) = parse(&files[0].1);
+        assert_eq!(reparsed.children[0].content_times[2], Duration::from_secs(110 * 60));
+    }
+
+    #[test]
+    fn unreadable_lines_are_reported() {
+        let buf = "\
+* TODO Running
+:LOGBOOK:
+CLOCK: [2026-10-18 Sun 09:00]
+CLOCK: [2026-10-18 Sun 09:00]--[2026-10-18 Sun 10:00] => soon
+:END:
+*TODO Missing space
+*** Too deep
+";
+        let (parsed, diagnostics) = parse(buf);
+        let lines: Vec<usize> = diagnostics.iter().map(|diagnostic| diagnostic.line).collect();
+        assert_eq!(lines, vec![3, 4, 6, 7]);
+        assert_eq!(parsed.content_times[0], Duration::from_secs(0));
+    }
+}
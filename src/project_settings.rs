@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+
+/// Per-project settings read from the front matter block at the top of the task file.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct ProjectSettings {
+    pub project_name: Option<String>,
+    pub save_interval: u64,
+    pub hourly_rate: Option<f64>,
+    pub exclude_headings: Vec<String>,
+    pub writeback: WritebackMode,
+    pub backup_retention: usize,
+    pub timesheet: bool,
+    pub annotate_headings: bool,
+    pub archive: ArchiveMode,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum WritebackMode {
+    /// Rewrite the task file on every save
+    #[default]
+    Overwrite,
+    /// Show a diff of the task file and ask before rewriting it
+    Confirm,
+    /// Never write to the task file
+    ReadOnly,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum ArchiveMode {
+    /// Move completed tasks under an `Archive` heading at the end of the task file
+    #[default]
+    Section,
+    /// Move completed tasks out to `ARCHIVE.md` next to the task file
+    File,
+}
+
+impl Default for ProjectSettings {
+    fn default() -> Self {
+        Self {
+            project_name: None,
+            save_interval: 60,
+            hourly_rate: None,
+            exclude_headings: Vec::new(),
+            writeback: WritebackMode::default(),
+            backup_retention: 10,
+            timesheet: false,
+            annotate_headings: false,
+            archive: ArchiveMode::default(),
+        }
+    }
+}
+
+impl ProjectSettings {
+    pub fn from_front_matter(front_matter: Option<&str>) -> Result<Self, String> {
+        let Some(front_matter) = front_matter else {
+            return Ok(ProjectSettings::default());
+        };
+
+        if front_matter.trim().is_empty() {
+            return Ok(ProjectSettings::default());
+        }
+
+        let settings: ProjectSettings =
+            serde_yaml::from_str(front_matter).map_err(|e| format!("Invalid front matter: {}", e))?;
+
+        // The app saves whenever this many seconds have passed, so zero would save on every tick
+        if settings.save_interval == 0 {
+            return Err("Invalid front matter: save_interval must be at least 1 second".to_string());
+        }
+
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::Node;
+
+    #[test]
+    fn reads_settings_from_front_matter() {
+        let buf = "\
+---
+project_name: Website
+hourly_rate: 85.5
+exclude_headings:
+  - Someday
+writeback: read-only
+---
+# Someday
+- [ ] Learn Rust
+";
+        let root = Node::convert_from(buf);
+
+        let settings = ProjectSettings::from_front_matter(root.front_matter.as_deref()).unwrap();
+        assert_eq!(settings.project_name.as_deref(), Some("Website"));
+        assert_eq!(settings.hourly_rate, Some(85.5));
+        assert_eq!(settings.exclude_headings, vec!["Someday"]);
+        assert_eq!(settings.writeback, WritebackMode::ReadOnly);
+        // Fields that are not set keep their defaults
+        assert_eq!(settings.save_interval, 60);
+        assert_eq!(settings.archive, ArchiveMode::Section);
+    }
+
+    #[test]
+    fn missing_or_empty_front_matter_is_the_default() {
+        assert_eq!(ProjectSettings::from_front_matter(None), Ok(ProjectSettings::default()));
+        assert_eq!(ProjectSettings::from_front_matter(Some("\n")), Ok(ProjectSettings::default()));
+        assert_eq!(Node::convert_from("---\n# Heading\n").front_matter, None);
+    }
+
+    #[test]
+    fn invalid_front_matter_is_an_error() {
+        assert!(ProjectSettings::from_front_matter(Some("save_interval: 0\n")).is_err());
+        assert!(ProjectSettings::from_front_matter(Some("save_interval: often\n")).is_err());
+        assert!(ProjectSettings::from_front_matter(Some("writeback: sometimes\n")).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::node::Node;
+use crate::reports::saved_state::{SavedState, heading_text};
+use crate::reports::timesheet::{OutputFormat, csv_field, format_seconds, join_headings};
+
+/// Estimated against actual time for the estimated work under one heading.
+#[derive(Serialize)]
+pub struct EstimateRow {
+    pub heading: String,
+    pub estimated_seconds: u64,
+    pub actual_seconds: u64,
+    /// Actual time as a percentage of the estimate
+    pub percent_used: u64,
+}
+
+impl EstimateRow {
+    fn new(heading: String, (estimate, actual): (Duration, Duration)) -> Self {
+        let estimated_seconds = estimate.as_secs();
+        let actual_seconds = actual.as_secs();
+        Self {
+            heading,
+            estimated_seconds,
+            actual_seconds,
+            percent_used: actual_seconds * 100 / estimated_seconds.max(1),
+        }
+    }
+}
+
+/// One row per heading with estimated tasks or an estimate of its own, then the project total.
+pub fn build_rows(state: &SavedState) -> Vec<EstimateRow> {
+    let mut rows = Vec::new();
+    for child in state.root_node.children.iter() {
+        push_rows(&mut rows, child, &mut Vec::new());
+    }
+
+    if let Some(totals) = state.root_node.estimate_totals() {
+        rows.push(EstimateRow::new(join_headings(&[]), totals));
+    }
+
+    rows
+}
+
+fn push_rows(rows: &mut Vec<EstimateRow>, node: &Node, heading_path: &mut Vec<String>) {
+    if node.excluded {
+        return;
+    }
+
+    heading_path.push(heading_text(node.heading.as_deref().unwrap_or_default()));
+
+    if let Some(totals) = node.estimate_totals() {
+        rows.push(EstimateRow::new(join_headings(heading_path), totals));
+    }
+    for child in node.children.iter() {
+        push_rows(rows, child, heading_path);
+    }
+
+    heading_path.pop();
+}
+
+pub fn render(rows: &[EstimateRow], output: OutputFormat) -> String {
+    match output {
+        OutputFormat::Table => render_table(rows),
+        OutputFormat::Csv => render_csv(rows),
+        OutputFormat::Json => format!("{}\n", serde_json::to_string_pretty(rows).unwrap()),
+    }
+}
+
+fn render_table(rows: &[EstimateRow]) -> String {
+    if rows.is_empty() {
+        return "No tasks or headings have an est: estimate\n".to_string();
+    }
+
+    let headers = ["Heading", "Estimate", "Actual", "Difference", "Used"];
+    let cells: Vec<[String; 5]> = rows
+        .iter()
+        .map(|row| {
+            let difference = if row.actual_seconds > row.estimated_seconds {
+                format!("+{}", format_seconds(row.actual_seconds - row.estimated_seconds))
+            } else {
+                format!("-{}", format_seconds(row.estimated_seconds - row.actual_seconds))
+            };
+
+            [
+                row.heading.clone(),
+                format_seconds(row.estimated_seconds),
+                format_seconds(row.actual_seconds),
+                difference,
+                format!("{}%", row.percent_used),
+            ]
+        })
+        .collect();
+
+    let mut widths = headers.map(|header| header.chars().count());
+    for row in cells.iter() {
+        for (idx, cell) in row.iter().enumerate() {
+            widths[idx] = widths[idx].max(cell.chars().count());
+        }
+    }
+
+    let print_row = |row: [&str; 5]| {
+        let padded: Vec<String> = row
+            .iter()
+            .enumerate()
+            .map(|(idx, cell)| format!("{:width$}", cell, width = widths[idx]))
+            .collect();
+        format!("{}\n", padded.join("  ").trim_end())
+    };
+
+    let mut table = print_row(headers);
+    table.push_str(&print_row(widths.map(|width| "-".repeat(width)).each_ref().map(|s| s.as_str())));
+    for row in cells.iter() {
+        table.push_str(&print_row(row.each_ref().map(|s| s.as_str())));
+    }
+
+    table
+}
+
+fn render_csv(rows: &[EstimateRow]) -> String {
+    let mut csv = "heading,estimated_seconds,actual_seconds,percent_used\n".to_string();
+    for row in rows {
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            csv_field(&row.heading),
+            row.estimated_seconds,
+            row.actual_seconds,
+            row.percent_used
+        ));
+    }
+
+    csv
+}
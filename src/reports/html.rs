@@ -0,0 +1,117 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::node::Node;
+use crate::reports::saved_state::{SavedState, TaskEntry, heading_text};
+use crate::reports::timesheet::format_seconds;
+
+const STYLE: &str = "
+body { font-family: sans-serif; max-width: 60rem; margin: 2rem auto; color: #222; }
+h1 { border-bottom: 1px solid #ccc; padding-bottom: 0.5rem; }
+ul.tree { list-style: none; padding-left: 1.5rem; }
+.row { display: grid; grid-template-columns: 18rem 4rem 1fr 6rem; gap: 0.5rem; align-items: center; padding: 0.15rem 0; }
+.bar { background: #eee; height: 0.8rem; border-radius: 0.2rem; }
+.bar > div { background: #4a7fb5; height: 100%; border-radius: 0.2rem; }
+.percent, .time { text-align: right; font-variant-numeric: tabular-nums; }
+table { border-collapse: collapse; width: 100%; }
+td, th { text-align: left; padding: 0.25rem 0.5rem; border-bottom: 1px solid #eee; }
+";
+
+/// Builds a single HTML page with inline styles, so it can be sent on without any other files.
+pub fn render(state: &SavedState, project_name: &str) -> String {
+    let total_time = state.root_node.rolled_up_time();
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{} report</title>\n", escape(project_name)));
+    html.push_str(&format!("<style>{}</style>\n</head>\n<body>\n", STYLE));
+    html.push_str(&format!("<h1>{}</h1>\n", escape(project_name)));
+    html.push_str(&format!(
+        "<p>Generated {}. Total tracked time {}.</p>\n",
+        chrono::Local::now().format("%Y-%m-%d %H:%M"),
+        format_seconds(total_time.as_secs())
+    ));
+
+    html.push_str("<h2>Headings</h2>\n<ul class=\"tree\">\n");
+    push_heading(&mut html, "All tasks", &state.root_node, total_time);
+    html.push_str("</ul>\n");
+
+    html.push_str("<h2>Completed tasks</h2>\n");
+    let completed: Vec<_> = state.tasks().into_iter().filter(|task| task.completed).collect();
+    if completed.is_empty() {
+        html.push_str("<p>No tasks have been completed yet.</p>\n");
+    } else {
+        html.push_str("<table>\n<tr><th>Task</th><th>Heading</th><th>Completed</th><th>Time</th></tr>\n");
+        for task in completed {
+            let completed_on = completion_date(state, &task).unwrap_or("-".to_string());
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                escape(&task.name),
+                escape(&task.heading_path.join(" / ")),
+                escape(&completed_on),
+                format_seconds(task.time.as_secs())
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// `<task file stem>-report.html`, next to the task file.
+pub fn default_output_path(file_path: &Path) -> PathBuf {
+    let stem = file_path.file_stem().and_then(|str| str.to_str()).unwrap_or("project");
+    file_path.with_file_name(format!("{}-report.html", stem))
+}
+
+fn push_heading(html: &mut String, title: &str, node: &Node, total_time: Duration) {
+    let (completed, total) = node.completion_counts();
+    let percent = completed * 100 / total.max(1);
+
+    let time = node.rolled_up_time();
+    let bar_width = if total_time.is_zero() {
+        0.0
+    } else {
+        time.as_secs_f64() / total_time.as_secs_f64() * 100.0
+    };
+
+    html.push_str(&format!(
+        "<li><div class=\"row\"><span>{}</span><span class=\"percent\">{}%</span>\
+         <div class=\"bar\"><div style=\"width: {:.1}%\"></div></div><span class=\"time\">{}</span></div>\n",
+        escape(title),
+        percent,
+        bar_width,
+        format_seconds(time.as_secs())
+    ));
+
+    let children: Vec<&Node> = node.children.iter().filter(|child| !child.excluded).collect();
+    if !children.is_empty() {
+        html.push_str("<ul class=\"tree\">\n");
+        for child in children {
+            let child_title = heading_text(child.heading.as_deref().unwrap_or_default());
+            push_heading(html, &child_title, child, total_time);
+        }
+        html.push_str("</ul>\n");
+    }
+
+    html.push_str("</li>\n");
+}
+
+/// The day the task was checked off, for tasks completed since completions were recorded.
+fn completion_date(state: &SavedState, task: &TaskEntry) -> Option<String> {
+    let completion = state
+        .completions
+        .iter()
+        .find(|completion| completion.heading_path == task.heading_path && completion.task == task.name)?;
+
+    Some(completion.time().format("%Y-%m-%d").to_string())
+}
+
+fn escape(text: &str) -> String {
+    text
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
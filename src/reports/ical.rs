@@ -0,0 +1,131 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+
+use crate::session::Session;
+
+const MAX_LINE_OCTETS: usize = 75;
+
+/// One `VEVENT` per recorded session, with times in UTC so calendars place them correctly.
+pub fn render(sessions: &[Session], project_name: &str) -> String {
+    let stamp = format_time(Utc::now());
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//unnamed//work sessions//EN".to_string(),
+        format!("X-WR-CALNAME:{}", escape(project_name)),
+    ];
+
+    for (idx, session) in sessions.iter().enumerate() {
+        let start = session.start_time().with_timezone(&Utc);
+        let end = session.end_time().with_timezone(&Utc);
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}-{}@{}", session.start, idx, escape(project_name)));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!("DTSTART:{}", format_time(start)));
+        lines.push(format!("DTEND:{}", format_time(end)));
+        lines.push(format!("SUMMARY:{}", escape(&session.task)));
+        lines.push(format!("DESCRIPTION:{}", escape(&session.heading_path.join(" / "))));
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold(line)).collect::<Vec<_>>().join("")
+}
+
+/// `<task file stem>.ics`, next to the task file.
+pub fn default_output_path(file_path: &Path) -> PathBuf {
+    file_path.with_extension("ics")
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn escape(text: &str) -> String {
+    text
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Lines longer than 75 octets continue on the next line after a single space.
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut line_octets = 0;
+
+    for ch in line.chars() {
+        if line_octets + ch.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            line_octets = 1;
+        }
+
+        folded.push(ch);
+        line_octets += ch.len_utf8();
+    }
+
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unfold(folded: &str) -> String {
+        folded.replace("\r\n ", "").trim_end_matches("\r\n").to_string()
+    }
+
+    #[test]
+    fn escapes_text_values() {
+        assert_eq!(escape("Plan; test, ship\\done\nnext"), "Plan\\; test\\, ship\\\\done\\nnext");
+    }
+
+    #[test]
+    fn folds_long_lines_at_75_octets() {
+        let line = format!("SUMMARY:{}", "a".repeat(200));
+        let folded = fold(&line);
+
+        assert!(folded.split("\r\n").all(|physical| physical.len() <= MAX_LINE_OCTETS));
+        assert_eq!(folded.matches("\r\n ").count(), 2);
+        assert_eq!(unfold(&folded), line);
+    }
+
+    #[test]
+    fn folding_does_not_split_characters() {
+        let line = format!("SUMMARY:{}", "é".repeat(60));
+        let folded = fold(&line);
+
+        for physical in folded.split("\r\n") {
+            assert!(physical.len() <= MAX_LINE_OCTETS);
+        }
+        assert_eq!(unfold(&folded), line);
+    }
+
+    #[test]
+    fn short_lines_are_not_folded() {
+        assert_eq!(fold("BEGIN:VCALENDAR"), "BEGIN:VCALENDAR\r\n");
+    }
+
+    #[test]
+    fn writes_an_event_per_session_in_utc() {
+        let session = Session {
+            heading_path: vec!["Backend".to_string(), "API".to_string()],
+            task: "Add the endpoint, then test it".to_string(),
+            start: 1_792_324_800,
+            end: 1_792_328_400,
+        };
+        let calendar = render(&[session.clone(), session], "Website");
+
+        assert_eq!(calendar.matches("BEGIN:VEVENT\r\n").count(), 2);
+        assert!(calendar.contains("DTSTART:20261018T120000Z\r\n"));
+        assert!(calendar.contains("DTEND:20261018T130000Z\r\n"));
+        assert!(calendar.contains("SUMMARY:Add the endpoint\\, then test it\r\n"));
+        assert!(calendar.contains("DESCRIPTION:Backend / API\r\n"));
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+    }
+}
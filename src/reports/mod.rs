@@ -0,0 +1,17 @@
+use strum_macros::{Display, EnumIter};
+
+pub mod estimates;
+pub mod html;
+pub mod ical;
+pub mod saved_state;
+pub mod timesheet;
+pub mod timewarrior;
+
+/// Files that can be exported from the TUI.
+#[derive(EnumIter, Display, Clone, Copy, PartialEq)]
+pub enum ExportKind {
+    #[strum(to_string = "HTML report")]
+    Html,
+    #[strum(to_string = "iCalendar sessions")]
+    Calendar,
+}
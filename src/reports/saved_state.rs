@@ -0,0 +1,118 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::app::App;
+use crate::node::Node;
+use crate::project_settings::ProjectSettings;
+use crate::session::{Adjustment, Completion, Session};
+
+/// The parts of a project's `save.json` that reports are built from.
+pub struct SavedState {
+    pub root_node: Node,
+    pub sessions: Vec<Session>,
+    pub adjustments: Vec<Adjustment>,
+    pub completions: Vec<Completion>,
+}
+
+#[derive(Deserialize)]
+struct SavedWindow {
+    task_view: SavedTaskView,
+}
+
+#[derive(Deserialize)]
+struct SavedTaskView {
+    root_node: Node,
+    #[serde(default)]
+    sessions: Vec<Session>,
+    #[serde(default)]
+    adjustments: Vec<Adjustment>,
+    #[serde(default)]
+    completions: Vec<Completion>,
+}
+
+/// A task with the headings above it, as shown in the breadcrumbs.
+pub struct TaskEntry {
+    pub heading_path: Vec<String>,
+    pub name: String,
+    pub time: Duration,
+    pub completed: bool,
+}
+
+impl SavedState {
+    pub fn load(file_name: &str) -> Result<Self, String> {
+        let file_path = canonical_path(file_name)?;
+        let save_file = format!("{}/save.json", App::save_dir_for(&file_path));
+        let Ok(save_contents) = fs::read_to_string(&save_file) else {
+            return Err(format!("No saved state found at {}", save_file));
+        };
+
+        let saved: SavedWindow = match serde_json::from_str(&save_contents) {
+            Ok(saved) => saved,
+            Err(e) => return Err(format!("Could not read {}: {}", save_file, e)),
+        };
+
+        Ok(Self {
+            root_node: saved.task_view.root_node,
+            sessions: saved.task_view.sessions,
+            adjustments: saved.task_view.adjustments,
+            completions: saved.task_view.completions,
+        })
+    }
+
+    /// The name from the front matter, falling back to the directory the task file is in.
+    pub fn project_name(&self, file_name: &str) -> String {
+        let settings = ProjectSettings::from_front_matter(self.root_node.front_matter.as_deref()).unwrap_or_default();
+        if let Some(name) = settings.project_name {
+            return name;
+        }
+
+        let file_path = canonical_path(file_name).unwrap_or_default();
+        file_path
+            .parent()
+            .and_then(|p| p.file_name())
+            .and_then(|str| str.to_str())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    pub fn tasks(&self) -> Vec<TaskEntry> {
+        let mut entries = Vec::new();
+        collect_tasks(&self.root_node, &mut Vec::new(), &mut entries);
+        entries
+    }
+}
+
+pub fn canonical_path(file_name: &str) -> Result<PathBuf, String> {
+    Path::new(file_name)
+        .canonicalize()
+        .map_err(|_| format!("Could not find file '{}'", file_name))
+}
+
+/// Heading text without its markers or estimate, as headings are named in sessions and reports.
+pub fn heading_text(heading: &str) -> String {
+    Node::without_estimate(heading.trim_start_matches(['#', '*']).trim())
+}
+
+fn collect_tasks(node: &Node, heading_path: &mut Vec<String>, entries: &mut Vec<TaskEntry>) {
+    if node.excluded {
+        return;
+    }
+
+    for idx in 0..node.content.len() {
+        entries.push(TaskEntry {
+            heading_path: heading_path.clone(),
+            name: Node::without_estimate(&node.content[idx]),
+            time: node.content_times[idx],
+            completed: node.completed_tasks[idx],
+        });
+    }
+
+    for child in node.children.iter() {
+        heading_path.push(heading_text(child.heading.as_deref().unwrap_or_default()));
+        collect_tasks(child, heading_path, entries);
+        heading_path.pop();
+    }
+}
@@ -0,0 +1,407 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+use strum_macros::{Display, EnumIter};
+
+use crate::node::Node;
+use crate::session::{Adjustment, Session};
+use crate::reports::saved_state::{SavedState, heading_text};
+
+const UNDATED: &str = "undated";
+const ROOT_HEADING: &str = "(root)";
+
+#[derive(EnumIter, Display, Clone, Copy, PartialEq)]
+pub enum Grouping {
+    #[strum(to_string = "day")]
+    Day,
+    #[strum(to_string = "week")]
+    Week,
+}
+
+#[derive(EnumIter, Display, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    #[strum(to_string = "table")]
+    Table,
+    #[strum(to_string = "csv")]
+    Csv,
+    #[strum(to_string = "json")]
+    Json,
+}
+
+pub struct ReportOptions {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub grouping: Grouping,
+    pub output: OutputFormat,
+}
+
+/// Time in one period, for a heading path when `task` is empty or for a single task.
+#[derive(Serialize)]
+pub struct ReportRow {
+    pub period: String,
+    pub heading: String,
+    pub task: Option<String>,
+    pub seconds: u64,
+}
+
+impl Grouping {
+    fn period(&self, date: NaiveDate) -> String {
+        match self {
+            Grouping::Day => date.format("%Y-%m-%d").to_string(),
+            Grouping::Week => {
+                let week = date.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+        }
+    }
+}
+
+/// Totals recorded sessions and time added by hand per period. Tracked time that neither
+/// accounts for, such as time from before sessions were recorded, is reported as undated when
+/// no range is given.
+pub fn build_rows(state: &SavedState, options: &ReportOptions) -> Vec<ReportRow> {
+    let mut totals: BTreeMap<(String, String, Option<String>), u64> = BTreeMap::new();
+
+    let mut add_time = |period: &str, heading_path: &[String], task: &str, seconds: u64| {
+        for depth in 0..=heading_path.len() {
+            let heading = join_headings(&heading_path[..depth]);
+            *totals.entry((period.to_string(), heading, None)).or_default() += seconds;
+        }
+
+        let task_key = (period.to_string(), join_headings(heading_path), Some(task.to_string()));
+        *totals.entry(task_key).or_default() += seconds;
+    };
+
+    let in_range = |date: NaiveDate| {
+        let before_range = options.from.is_some_and(|from| date < from);
+        let after_range = options.to.is_some_and(|to| date > to);
+        !before_range && !after_range
+    };
+
+    for session in state.sessions.iter() {
+        let date = session.start_time().date_naive();
+        if in_range(date) {
+            let period = options.grouping.period(date);
+            add_time(&period, &session.heading_path, &session.task, session.duration_secs());
+        }
+    }
+
+    for adjustment in state.adjustments.iter().filter(|adjustment| adjustment.added_secs() > 0) {
+        let date = adjustment.time().date_naive();
+        if in_range(date) {
+            let period = options.grouping.period(date);
+            add_time(&period, &adjustment.heading_path, &adjustment.task, adjustment.added_secs());
+        }
+    }
+
+    if options.from.is_none() && options.to.is_none() {
+        for task in state.tasks() {
+            let session_secs: u64 = state
+                .sessions
+                .iter()
+                .filter(|session| session.heading_path == task.heading_path && session.task == task.name)
+                .map(|session| session.duration_secs())
+                .sum();
+            let added_secs: u64 = state
+                .adjustments
+                .iter()
+                .filter(|adjustment| adjustment.heading_path == task.heading_path && adjustment.task == task.name)
+                .map(|adjustment| adjustment.added_secs())
+                .sum();
+
+            let undated_secs = task.time.as_secs().saturating_sub(session_secs + added_secs);
+            if undated_secs > 0 {
+                add_time(UNDATED, &task.heading_path, &task.name, undated_secs);
+            }
+        }
+    }
+
+    totals
+        .into_iter()
+        .map(|((period, heading, task), seconds)| ReportRow {
+            period,
+            heading,
+            task,
+            seconds,
+        })
+        .collect()
+}
+
+pub fn render(rows: &[ReportRow], output: OutputFormat) -> String {
+    match output {
+        OutputFormat::Table => render_table(rows),
+        OutputFormat::Csv => render_csv(rows),
+        OutputFormat::Json => format!("{}\n", serde_json::to_string_pretty(rows).unwrap()),
+    }
+}
+
+fn render_table(rows: &[ReportRow]) -> String {
+    if rows.is_empty() {
+        return "No tracked time in the given range\n".to_string();
+    }
+
+    let headers = ["Period", "Heading", "Task", "Time"];
+    let cells: Vec<[String; 4]> = rows
+        .iter()
+        .map(|row| {
+            [
+                row.period.clone(),
+                row.heading.clone(),
+                row.task.clone().unwrap_or_default(),
+                format_seconds(row.seconds),
+            ]
+        })
+        .collect();
+
+    let mut widths = headers.map(|header| header.chars().count());
+    for row in cells.iter() {
+        for (idx, cell) in row.iter().enumerate() {
+            widths[idx] = widths[idx].max(cell.chars().count());
+        }
+    }
+
+    let print_row = |row: [&str; 4]| {
+        let padded: Vec<String> = row
+            .iter()
+            .enumerate()
+            .map(|(idx, cell)| format!("{:width$}", cell, width = widths[idx]))
+            .collect();
+        format!("{}\n", padded.join("  ").trim_end())
+    };
+
+    let mut table = print_row(headers);
+    table.push_str(&print_row(widths.map(|width| "-".repeat(width)).each_ref().map(|s| s.as_str())));
+
+    let mut last_period = String::new();
+    for row in cells.iter() {
+        let period = if row[0] == last_period { "" } else { row[0].as_str() };
+        table.push_str(&print_row([period, &row[1], &row[2], &row[3]]));
+        last_period = row[0].clone();
+    }
+
+    table
+}
+
+fn render_csv(rows: &[ReportRow]) -> String {
+    let mut csv = "period,heading,task,seconds\n".to_string();
+    for row in rows {
+        let task = row.task.clone().unwrap_or_default();
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            csv_field(&row.period),
+            csv_field(&row.heading),
+            csv_field(&task),
+            row.seconds
+        ));
+    }
+
+    csv
+}
+
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        return format!("\"{}\"", field.replace('"', "\"\""));
+    }
+
+    field.to_string()
+}
+
+/// The contents of `TIMESHEET.md`: time and task counts per heading, then each day's sessions.
+pub fn render_markdown(state: &SavedState, project_name: &str) -> String {
+    let mut markdown = format!("# Timesheet: {}\n\n", project_name);
+    markdown.push_str("_Generated on every save. Edits to this file are overwritten._\n\n");
+
+    markdown.push_str("## Summary\n\n");
+    markdown.push_str("| Heading | Time | Done | Remaining |\n");
+    markdown.push_str("| --- | --- | --- | --- |\n");
+    for child in state.root_node.children.iter() {
+        push_summary_row(&mut markdown, child, &mut Vec::new());
+    }
+
+    let (completed, total) = state.root_node.completion_counts();
+    markdown.push_str(&format!(
+        "| **Total** | {} | {} | {} |\n",
+        format_seconds(state.root_node.rolled_up_time().as_secs()),
+        completed,
+        total - completed
+    ));
+
+    markdown.push_str("\n## Daily log\n");
+    let mut days: BTreeMap<NaiveDate, (Vec<&Session>, Vec<&Adjustment>)> = BTreeMap::new();
+    for session in state.sessions.iter() {
+        days.entry(session.start_time().date_naive()).or_default().0.push(session);
+    }
+    for adjustment in state.adjustments.iter() {
+        days.entry(adjustment.time().date_naive()).or_default().1.push(adjustment);
+    }
+
+    if days.is_empty() {
+        markdown.push_str("\nNo timer sessions have been recorded yet.\n");
+    }
+
+    for (day, (sessions, adjustments)) in days.iter().rev() {
+        let session_total: u64 = sessions.iter().map(|session| session.duration_secs()).sum();
+        let added_total: u64 = adjustments.iter().map(|adjustment| adjustment.added_secs()).sum();
+        let day_total = session_total + added_total;
+        markdown.push_str(&format!("\n### {} ({})\n\n", day.format("%Y-%m-%d"), format_seconds(day_total)));
+
+        for session in sessions {
+            markdown.push_str(&format!(
+                "- {}-{} {} ({}): {}\n",
+                session.start_time().format("%H:%M"),
+                session.end_time().format("%H:%M"),
+                session.task,
+                join_headings(&session.heading_path),
+                format_seconds(session.duration_secs())
+            ));
+        }
+
+        for adjustment in adjustments {
+            markdown.push_str(&format!(
+                "- {} {} ({}): set by hand from {} to {}\n",
+                adjustment.time().format("%H:%M"),
+                adjustment.task,
+                join_headings(&adjustment.heading_path),
+                format_seconds(adjustment.previous.as_secs()),
+                format_seconds(adjustment.adjusted.as_secs())
+            ));
+        }
+    }
+
+    markdown
+}
+
+fn push_summary_row(markdown: &mut String, node: &Node, heading_path: &mut Vec<String>) {
+    if node.excluded {
+        return;
+    }
+
+    heading_path.push(heading_text(node.heading.as_deref().unwrap_or_default()));
+
+    let (completed, total) = node.completion_counts();
+    markdown.push_str(&format!(
+        "| {} | {} | {} | {} |\n",
+        join_headings(heading_path).replace('|', "\\|"),
+        format_seconds(node.rolled_up_time().as_secs()),
+        completed,
+        total - completed
+    ));
+
+    for child in node.children.iter() {
+        push_summary_row(markdown, child, heading_path);
+    }
+
+    heading_path.pop();
+}
+
+pub fn join_headings(heading_path: &[String]) -> String {
+    if heading_path.is_empty() {
+        return ROOT_HEADING.to_string();
+    }
+
+    heading_path.join(" / ")
+}
+
+pub fn format_seconds(seconds: u64) -> String {
+    format!("{:02}:{:02}:{:02}", seconds / 3600, (seconds % 3600) / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::{Local, TimeZone};
+
+    use super::*;
+
+    fn session(day: u32, hour: u32, minutes: i64) -> Session {
+        let start = Local.with_ymd_and_hms(2026, 10, day, hour, 0, 0).unwrap().timestamp();
+        Session {
+            heading_path: vec!["Backend".to_string()],
+            task: "Add the endpoint".to_string(),
+            start,
+            end: start + minutes * 60,
+        }
+    }
+
+    /// A task with 3h tracked: 2h30m of it over three sessions in two ISO weeks and the rest
+    /// from before sessions were recorded.
+    fn state() -> SavedState {
+        let mut root_node = Node::convert_from("# Backend\n- [ ] Add the endpoint\n");
+        root_node.children[0].content_times[0] = Duration::from_secs(3 * 3600);
+
+        SavedState {
+            root_node,
+            // Monday and Tuesday of 2026-W42, then Monday of 2026-W43
+            sessions: vec![session(12, 9, 60), session(13, 14, 30), session(19, 10, 60)],
+            adjustments: Vec::new(),
+            completions: Vec::new(),
+        }
+    }
+
+    fn task_rows(rows: &[ReportRow]) -> Vec<(&str, u64)> {
+        rows.iter()
+            .filter(|row| row.task.is_some())
+            .map(|row| (row.period.as_str(), row.seconds / 60))
+            .collect()
+    }
+
+    fn options(grouping: Grouping, from: Option<NaiveDate>, to: Option<NaiveDate>) -> ReportOptions {
+        ReportOptions {
+            from,
+            to,
+            grouping,
+            output: OutputFormat::Table,
+        }
+    }
+
+    #[test]
+    fn groups_sessions_by_day() {
+        let rows = build_rows(&state(), &options(Grouping::Day, None, None));
+        assert_eq!(
+            task_rows(&rows),
+            vec![("2026-10-12", 60), ("2026-10-13", 30), ("2026-10-19", 60), (UNDATED, 30)]
+        );
+
+        // Each task's time also counts towards every heading above it
+        let root_total: u64 = rows.iter().filter(|row| row.heading == ROOT_HEADING).map(|row| row.seconds).sum();
+        assert_eq!(root_total, 3 * 3600);
+        assert!(rows.iter().any(|row| row.heading == "Backend" && row.task.is_none()));
+    }
+
+    #[test]
+    fn groups_sessions_by_iso_week() {
+        let rows = build_rows(&state(), &options(Grouping::Week, None, None));
+        assert_eq!(task_rows(&rows), vec![("2026-W42", 90), ("2026-W43", 60), (UNDATED, 30)]);
+    }
+
+    #[test]
+    fn range_leaves_out_other_days_and_undated_time() {
+        let from = NaiveDate::from_ymd_opt(2026, 10, 13);
+        let to = NaiveDate::from_ymd_opt(2026, 10, 18);
+        let rows = build_rows(&state(), &options(Grouping::Day, from, to));
+        assert_eq!(task_rows(&rows), vec![("2026-10-13", 30)]);
+    }
+
+    #[test]
+    fn time_added_by_hand_counts_on_the_day_it_was_added() {
+        let adjustment = |day: u32, previous: u64, adjusted: u64| Adjustment {
+            heading_path: vec!["Backend".to_string()],
+            task: "Add the endpoint".to_string(),
+            at: Local.with_ymd_and_hms(2026, 10, day, 12, 0, 0).unwrap().timestamp(),
+            previous: Duration::from_secs(previous * 60),
+            adjusted: Duration::from_secs(adjusted * 60),
+        };
+
+        let mut state = state();
+        // 20 minutes added on the Wednesday and 10 taken away on the Thursday
+        state.adjustments = vec![adjustment(14, 150, 170), adjustment(15, 170, 160)];
+
+        let rows = build_rows(&state, &options(Grouping::Day, None, None));
+        assert_eq!(
+            task_rows(&rows),
+            vec![("2026-10-12", 60), ("2026-10-13", 30), ("2026-10-14", 20), ("2026-10-19", 60), (UNDATED, 10)]
+        );
+    }
+}
@@ -0,0 +1,345 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::node::{Node, NodePath};
+use crate::session::Session;
+
+const TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// A closed interval read from a Timewarrior data file.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Interval {
+    pub start: i64,
+    pub end: i64,
+    pub tags: Vec<String>,
+}
+
+impl Interval {
+    /// Day, local start and end time and tags, for listing the interval in the app.
+    pub fn describe(&self) -> String {
+        let local = |timestamp| DateTime::from_timestamp(timestamp, 0).unwrap_or_default().with_timezone(&Local);
+        let (start, end) = (local(self.start), local(self.end));
+
+        format!(
+            "{} {}-{}  {}",
+            start.format("%Y-%m-%d"),
+            start.format("%H:%M"),
+            end.format("%H:%M"),
+            self.tags.join(" ")
+        )
+    }
+}
+
+/// Where an interval's time belongs in the task tree.
+pub struct IntervalMatch {
+    pub heading_path: Vec<String>,
+    pub node_path: NodePath,
+    pub task_idx: usize,
+}
+
+/// Timewarrior keeps its database in `$TIMEWARRIORDB`, or `~/.timewarrior` when unset.
+pub fn default_data_dir() -> PathBuf {
+    let db_dir = match std::env::var("TIMEWARRIORDB") {
+        Ok(dir) => PathBuf::from(dir),
+        Err(_) => std::env::home_dir().unwrap().join(".timewarrior"),
+    };
+
+    db_dir.join("data")
+}
+
+/// The heading path followed by the task name, so each level can be filtered on in Timewarrior.
+pub fn session_tags(session: &Session) -> Vec<String> {
+    let mut tags: Vec<String> = session.heading_path.iter().filter(|heading| !heading.is_empty()).cloned().collect();
+    tags.push(session.task.clone());
+    tags
+}
+
+pub fn interval_line(start: i64, end: i64, tags: &[String]) -> String {
+    let tags: Vec<String> = tags.iter().map(|tag| quote_tag(tag)).collect();
+    format!("inc {} - {} # {}", format_time(start), format_time(end), tags.join(" "))
+}
+
+/// Writes the sessions into the monthly `YYYY-MM.data` files in `data_dir`. Intervals that are
+/// already in a file are not written again, so exporting twice is harmless.
+pub fn export(sessions: &[Session], data_dir: &Path) -> Result<usize, String> {
+    let mut months: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for session in sessions.iter() {
+        let Some(start) = DateTime::from_timestamp(session.start, 0) else {
+            continue;
+        };
+
+        let line = interval_line(session.start, session.end, &session_tags(session));
+        months.entry(start.format("%Y-%m").to_string()).or_default().push(line);
+    }
+
+    if let Err(e) = fs::create_dir_all(data_dir) {
+        return Err(format!("Could not create {}: {}", data_dir.display(), e));
+    }
+
+    let mut written = 0;
+    for (month, lines) in months {
+        let data_file = data_dir.join(format!("{}.data", month));
+        let existing = fs::read_to_string(&data_file).unwrap_or_default();
+
+        let mut file_lines: Vec<String> = existing.lines().map(str::to_string).collect();
+        for line in lines {
+            if !file_lines.contains(&line) {
+                file_lines.push(line);
+                written += 1;
+            }
+        }
+
+        // Timewarrior expects the intervals in a file to be in start order
+        file_lines.sort();
+        if let Err(e) = fs::write(&data_file, format!("{}\n", file_lines.join("\n"))) {
+            return Err(format!("Could not write {}: {}", data_file.display(), e));
+        }
+    }
+
+    Ok(written)
+}
+
+/// Reads a single data file, or every `.data` file when `path` is a directory.
+pub fn read_intervals(path: &Path) -> Result<Vec<Interval>, String> {
+    let mut data_files = Vec::new();
+    if path.is_dir() {
+        let Ok(entries) = fs::read_dir(path) else {
+            return Err(format!("Could not read {}", path.display()));
+        };
+
+        data_files = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|entry| entry.extension().is_some_and(|ext| ext == "data"))
+            .collect();
+        data_files.sort();
+    } else {
+        data_files.push(path.to_path_buf());
+    }
+
+    let mut intervals = Vec::new();
+    for data_file in data_files {
+        let Ok(contents) = fs::read_to_string(&data_file) else {
+            return Err(format!("Could not read {}", data_file.display()));
+        };
+
+        intervals.extend(contents.lines().filter_map(parse_line));
+    }
+
+    Ok(intervals)
+}
+
+/// Parses `inc <start> - <end> # <tags> [# <annotation>]`. Open intervals are skipped since
+/// they have no time to attach yet.
+pub fn parse_line(line: &str) -> Option<Interval> {
+    let rest = line.trim().strip_prefix("inc ")?;
+    let (range, tags) = match rest.split_once(" # ") {
+        Some((range, tags)) => (range, tags),
+        None => (rest, ""),
+    };
+
+    let (start, end) = range.trim().split_once(" - ")?;
+    Some(Interval {
+        start: parse_time(start.trim())?,
+        end: parse_time(end.trim())?,
+        tags: parse_tags(tags),
+    })
+}
+
+/// Finds the task an interval was tracked against. The tags are first read as a heading path
+/// ending in the task name; failing that, a single task whose name and headings are all among
+/// the tags is used.
+pub fn match_interval(root_node: &Node, interval: &Interval) -> Option<IntervalMatch> {
+    let locations = root_node.task_locations();
+    let task_name =
+        |node_path: &NodePath, idx: usize| root_node.get_node(node_path).map(|node| Node::without_estimate(&node.content[idx]));
+
+    if let Some((task, headings)) = interval.tags.split_last() {
+        let exact = locations.iter().find(|(heading_path, node_path, idx)| {
+            heading_path.iter().filter(|heading| !heading.is_empty()).eq(headings.iter())
+                && task_name(node_path, *idx).as_ref() == Some(task)
+        });
+
+        if let Some((heading_path, node_path, idx)) = exact {
+            return Some(IntervalMatch {
+                heading_path: heading_path.clone(),
+                node_path: node_path.clone(),
+                task_idx: *idx,
+            });
+        }
+    }
+
+    let candidates: Vec<_> = locations
+        .iter()
+        .filter(|(heading_path, node_path, idx)| {
+            let name_tagged = task_name(node_path, *idx).is_some_and(|name| interval.tags.contains(&name));
+            let headings_tagged = heading_path
+                .iter()
+                .filter(|heading| !heading.is_empty())
+                .all(|heading| interval.tags.contains(heading));
+            name_tagged && headings_tagged
+        })
+        .collect();
+
+    if let [(heading_path, node_path, idx)] = candidates.as_slice() {
+        return Some(IntervalMatch {
+            heading_path: heading_path.clone(),
+            node_path: node_path.clone(),
+            task_idx: *idx,
+        });
+    }
+
+    None
+}
+
+fn format_time(timestamp: i64) -> String {
+    DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .format(TIME_FORMAT)
+        .to_string()
+}
+
+fn parse_time(time: &str) -> Option<i64> {
+    let time = NaiveDateTime::parse_from_str(time, TIME_FORMAT).ok()?;
+    Some(DateTime::<Utc>::from_naive_utc_and_offset(time, Utc).timestamp())
+}
+
+fn quote_tag(tag: &str) -> String {
+    if tag.contains([' ', '"', '#']) || tag.is_empty() {
+        return format!("\"{}\"", tag.replace('\\', "\\\\").replace('"', "\\\""));
+    }
+
+    tag.to_string()
+}
+
+fn parse_tags(tags: &str) -> Vec<String> {
+    let mut parsed = Vec::new();
+    let mut chars = tags.trim().chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch == ' ' {
+            continue;
+        }
+
+        // A second `#` starts the annotation, which is not a tag
+        if ch == '#' {
+            break;
+        }
+
+        let mut tag = String::new();
+        if ch == '"' {
+            while let Some(ch) = chars.next() {
+                match ch {
+                    '\\' => tag.extend(chars.next()),
+                    '"' => break,
+                    _ => tag.push(ch),
+                }
+            }
+        } else {
+            tag.push(ch);
+            while let Some(&ch) = chars.peek() {
+                if ch == ' ' {
+                    break;
+                }
+                tag.push(ch);
+                chars.next();
+            }
+        }
+
+        parsed.push(tag);
+    }
+
+    parsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TASKS: &str = "\
+# Backend
+- [ ] Write tests
+## API
+- [ ] Add the endpoint
+- [ ] Write tests
+# Frontend
+- [ ] Build the form
+";
+
+    fn interval(tags: &[&str]) -> Interval {
+        Interval {
+            start: 1_792_324_800,
+            end: 1_792_328_400,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        }
+    }
+
+    fn matched(root_node: &Node, tags: &[&str]) -> Option<(Vec<String>, NodePath, usize)> {
+        match_interval(root_node, &interval(tags)).map(|found| (found.heading_path, found.node_path, found.task_idx))
+    }
+
+    #[test]
+    fn interval_lines_round_trip() {
+        let tags = vec!["Backend".to_string(), "Add the \"users\" endpoint".to_string()];
+        let line = interval_line(1_792_324_800, 1_792_328_400, &tags);
+        assert_eq!(line, "inc 20261018T120000Z - 20261018T130000Z # Backend \"Add the \\\"users\\\" endpoint\"");
+        assert_eq!(parse_line(&line), Some(Interval { start: 1_792_324_800, end: 1_792_328_400, tags }));
+    }
+
+    #[test]
+    fn open_intervals_and_annotations() {
+        assert_eq!(parse_line("inc 20261018T120000Z # Backend"), None);
+
+        let parsed = parse_line("inc 20261018T120000Z - 20261018T130000Z # Backend API # \"standup ran long\"");
+        assert_eq!(parsed.unwrap().tags, vec!["Backend", "API"]);
+    }
+
+    #[test]
+    fn matches_the_heading_path_first() {
+        let root_node = Node::convert_from(TASKS);
+
+        let backend = vec!["Backend".to_string()];
+        assert_eq!(matched(&root_node, &["Backend", "Write tests"]), Some((backend, vec![0], 0)));
+
+        let api = vec!["Backend".to_string(), "API".to_string()];
+        assert_eq!(matched(&root_node, &["Backend", "API", "Write tests"]), Some((api, vec![0, 0], 1)));
+    }
+
+    #[test]
+    fn falls_back_to_a_single_task_among_the_tags() {
+        let root_node = Node::convert_from(TASKS);
+
+        let frontend = vec!["Frontend".to_string()];
+        assert_eq!(matched(&root_node, &["Build the form", "client", "Frontend"]), Some((frontend, vec![1], 0)));
+
+        // Both `Write tests` tasks have all of their headings among the tags
+        assert_eq!(matched(&root_node, &["API", "Write tests", "Backend", "client"]), None);
+        assert_eq!(matched(&root_node, &["Build the form"]), None);
+    }
+
+    #[test]
+    fn export_skips_intervals_already_written() {
+        let data_dir = std::env::temp_dir().join(format!("timewarrior-test-{}", std::process::id()));
+        let session = |start: i64, task: &str| Session {
+            heading_path: vec!["Backend".to_string()],
+            task: task.to_string(),
+            start,
+            end: start + 3600,
+        };
+        let first = vec![session(1_792_324_800, "Write tests")];
+        let both = vec![session(1_792_324_800, "Write tests"), session(1_792_000_000, "Plan")];
+
+        assert_eq!(export(&first, &data_dir), Ok(1));
+        assert_eq!(export(&both, &data_dir), Ok(1));
+        assert_eq!(export(&both, &data_dir), Ok(0));
+
+        let intervals = read_intervals(&data_dir).unwrap();
+        fs::remove_dir_all(&data_dir).unwrap();
+
+        let tags: Vec<Vec<String>> = intervals.into_iter().map(|interval| interval.tags).collect();
+        assert_eq!(tags, vec![vec!["Backend", "Plan"], vec!["Backend", "Write tests"]]);
+    }
+}
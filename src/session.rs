@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// One stretch of time spent on a task, from starting its timer to stopping it.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Session {
+    pub heading_path: Vec<String>,
+    pub task: String,
+    pub start: i64,
+    pub end: i64,
+}
+
+impl Session {
+    pub fn start_time(&self) -> DateTime<Local> {
+        DateTime::from_timestamp(self.start, 0).unwrap_or_default().with_timezone(&Local)
+    }
+
+    pub fn end_time(&self) -> DateTime<Local> {
+        DateTime::from_timestamp(self.end, 0).unwrap_or_default().with_timezone(&Local)
+    }
+
+    pub fn duration_secs(&self) -> u64 {
+        self.end.saturating_sub(self.start).max(0) as u64
+    }
+}
+
+/// A time tracked on a task that was set by hand rather than by its timer.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Adjustment {
+    pub heading_path: Vec<String>,
+    pub task: String,
+    /// When the adjustment was made
+    pub at: i64,
+    pub previous: Duration,
+    pub adjusted: Duration,
+}
+
+impl Adjustment {
+    pub fn time(&self) -> DateTime<Local> {
+        DateTime::from_timestamp(self.at, 0).unwrap_or_default().with_timezone(&Local)
+    }
+
+    /// Time the adjustment added, which is zero when it took time away.
+    pub fn added_secs(&self) -> u64 {
+        self.adjusted.saturating_sub(self.previous).as_secs()
+    }
+}
+
+/// When a task was marked complete. Unchecking the task drops it again.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Completion {
+    pub heading_path: Vec<String>,
+    pub task: String,
+    pub at: i64,
+}
+
+impl Completion {
+    pub fn time(&self) -> DateTime<Local> {
+        DateTime::from_timestamp(self.at, 0).unwrap_or_default().with_timezone(&Local)
+    }
+}
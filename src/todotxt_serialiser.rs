@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{Local, NaiveDate};
+
+use crate::diagnostic::Diagnostic;
+use crate::node::{DetachedTask, Node, TaskMetadata, TaskStyle};
+use crate::traits::TaskFormat;
+
+/// todo.txt backend. Tasks are grouped under a heading per `+project`, then per `@context`.
+/// The `(A)` priority and `key:value` pairs are kept as the task's metadata and the rest of
+/// each line as its name, apart from the `x` completion marker, the completion date and the
+/// `time:` key that holds its tracked time.
+pub struct TodoTxtFormat;
+
+struct TodoLine {
+    completed: bool,
+    completion_date: Option<String>,
+    description: String,
+    metadata: TaskMetadata,
+    time: Duration,
+    /// `time:` value that is not a duration, which stays in the description
+    invalid_time: Option<String>,
+}
+
+impl TaskFormat for TodoTxtFormat {
+    fn parse(&self, buf: &str, _file_path: &Path) -> (Node, Vec<Diagnostic>) {
+        let mut root = Node::new();
+        let mut diagnostics = Vec::new();
+
+        for (line_idx, line) in buf.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            let todo = TodoLine::parse(line);
+
+            let mut path = Vec::new();
+            let mut node = &mut root;
+            let headings = [first_token(&todo.description, '+'), first_token(&todo.description, '@')];
+            for heading in headings.into_iter().flatten() {
+                let idx = child_heading(node, heading);
+                path.push(idx);
+                node = &mut node.children[idx];
+            }
+
+            if let Some(value) = &todo.invalid_time {
+                let message = format!("Tracked time '{}' is not a duration like 1h20m and is left in the task", value);
+                diagnostics.push(Diagnostic::new(line_idx + 1, &message, &path, Some(node.content.len())));
+            }
+
+            node.push_task(DetachedTask {
+                name: todo.description,
+                time: todo.time,
+                completed: todo.completed,
+                style: TaskStyle::default(),
+                metadata: todo.metadata,
+            });
+        }
+
+        (root, diagnostics)
+    }
+
+    fn serialise(&self, root_node: &Node, file_path: &Path) -> Vec<(PathBuf, String)> {
+        let existing = fs::read_to_string(file_path).unwrap_or_default();
+
+        // Original position and completion date of each line, so the file keeps its order
+        let mut originals: HashMap<String, (usize, Option<String>)> = HashMap::new();
+        for (idx, line) in existing.lines().filter(|line| !line.trim().is_empty()).enumerate() {
+            let todo = TodoLine::parse(line);
+            originals.entry(todo.description).or_insert((idx, todo.completion_date));
+        }
+
+        let mut todos: Vec<TodoLine> = Vec::new();
+        collect_tasks(root_node, &mut todos);
+
+        for todo in todos.iter_mut().filter(|todo| todo.completed) {
+            todo.completion_date = originals
+                .get(&todo.description)
+                .and_then(|(_, date)| date.clone())
+                .or_else(|| Some(Local::now().format("%Y-%m-%d").to_string()));
+        }
+
+        todos.sort_by_key(|todo| originals.get(&todo.description).map_or(usize::MAX, |(idx, _)| *idx));
+
+        let mut contents = String::new();
+        for todo in todos.iter() {
+            contents.push_str(&format!("{}\n", todo.print()));
+        }
+
+        vec![(file_path.to_path_buf(), contents)]
+    }
+
+    fn fixed_layout(&self) -> bool {
+        true
+    }
+}
+
+/// Whether `buf` reads as todo.txt rather than notes or markdown: no markdown headings or
+/// checkboxes, and at least one line with a completion marker, priority, project, context or
+/// `key:value` pair.
+pub fn looks_like_todotxt(buf: &str) -> bool {
+    let lines: Vec<&str> = buf.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+
+    let markdown = lines
+        .iter()
+        .any(|line| line.starts_with('#') || line.starts_with("- [") || line.starts_with("* [") || *line == "---");
+    if markdown {
+        return false;
+    }
+
+    lines.iter().any(|line| {
+        let todo = TodoLine::parse(line);
+        todo.completed
+            || !todo.metadata.is_empty()
+            || todo.time.as_secs() > 0
+            || first_token(&todo.description, '+').is_some()
+            || first_token(&todo.description, '@').is_some()
+    })
+}
+
+impl TodoLine {
+    fn parse(line: &str) -> Self {
+        let mut rest = line.trim();
+
+        let completed = rest.starts_with("x ");
+        let mut completion_date = None;
+        if completed {
+            rest = rest[2..].trim_start();
+
+            if let Some((date, remainder)) = rest.split_once(' ')
+                && NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok()
+            {
+                completion_date = Some(date.to_string());
+                rest = remainder.trim_start();
+            }
+        }
+
+        let mut metadata = TaskMetadata::default();
+        if let Some((priority, remainder)) = rest.split_once(' ')
+            && let [b'(', letter @ b'A'..=b'Z', b')'] = priority.as_bytes()
+        {
+            metadata.priority = Some(*letter as char);
+            rest = remainder.trim_start();
+        }
+
+        let mut time = Duration::default();
+        let mut invalid_time = None;
+        let mut words: Vec<&str> = Vec::new();
+        for word in rest.split_whitespace() {
+            match word.split_once(':') {
+                Some(("time", value)) => match TodoLine::tracked_time(value) {
+                    Some(tracked) => time = tracked,
+                    None => {
+                        invalid_time = Some(value.to_string());
+                        words.push(word);
+                    }
+                },
+                Some((key, value)) if is_field(key, value) => {
+                    metadata.fields.push((key.to_string(), value.to_string()));
+                }
+                _ => words.push(word),
+            }
+        }
+
+        Self {
+            completed,
+            completion_date,
+            description: words.join(" "),
+            metadata,
+            time,
+            invalid_time,
+        }
+    }
+
+    /// Reads a `time:` value, written by `print` like `1h20m` or as a bare number of seconds.
+    fn tracked_time(value: &str) -> Option<Duration> {
+        if let Ok(secs) = value.parse::<u64>() {
+            return Some(Duration::from_secs(secs));
+        }
+        Node::parse_duration(value)
+    }
+
+    fn print(&self) -> String {
+        let mut line = String::new();
+        if self.completed {
+            line.push_str("x ");
+            if let Some(date) = &self.completion_date {
+                line.push_str(&format!("{} ", date));
+            }
+        }
+
+        if let Some(priority) = self.metadata.priority {
+            line.push_str(&format!("({}) ", priority));
+        }
+
+        line.push_str(&self.description);
+
+        for (key, value) in self.metadata.fields.iter() {
+            line.push_str(&format!(" {}:{}", key, value));
+        }
+
+        if self.time.as_secs() > 0 {
+            line.push_str(&format!(" time:{}", Node::format_short_duration(self.time)));
+        }
+
+        line
+    }
+}
+
+fn collect_tasks(node: &Node, todos: &mut Vec<TodoLine>) {
+    for idx in 0..node.content.len() {
+        todos.push(TodoLine {
+            completed: node.completed_tasks[idx],
+            completion_date: None,
+            description: node.content[idx].clone(),
+            metadata: node.task_metadata(idx),
+            time: node.content_times[idx],
+            invalid_time: None,
+        });
+    }
+
+    for child_node in node.children.iter() {
+        collect_tasks(child_node, todos);
+    }
+}
+
+/// A `key:value` pair, as opposed to a URL or a word that only contains a colon. Estimates
+/// stay in the name, where the estimate popup edits them.
+fn is_field(key: &str, value: &str) -> bool {
+    let key_valid = !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    let value_valid = !value.is_empty() && !value.contains(':') && !value.starts_with('/');
+    key_valid && value_valid && key != "est"
+}
+
+fn first_token(description: &str, prefix: char) -> Option<&str> {
+    description
+        .split_whitespace()
+        .find(|word| word.len() > 1 && word.starts_with(prefix))
+}
+
+fn child_heading(node: &mut Node, heading: &str) -> usize {
+    match node.children.iter().position(|child| child.heading.as_deref() == Some(heading)) {
+        Some(idx) => idx,
+        None => {
+            node.children.push(Node::new_with_heading(heading.to_string()));
+            node.children.len() - 1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TASKS: &str = "\
+(A) Call the bank +Admin @phone due:2026-10-20
+Sketch the landing page +Website time:1h20m
+x 2026-10-17 Renew the domain +Website @laptop time:15m
+Water the plants
+";
+
+    fn write_temp(name: &str, contents: &str) -> PathBuf {
+        let file_path = std::env::temp_dir().join(format!("todotxt-test-{}-{}.txt", std::process::id(), name));
+        fs::write(&file_path, contents).unwrap();
+        file_path
+    }
+
+    #[test]
+    fn parse_serialise_parse_round_trips() {
+        let file_path = write_temp("round-trip", TASKS);
+
+        let (parsed, diagnostics) = TodoTxtFormat.parse(TASKS, &file_path);
+        assert!(diagnostics.is_empty());
+        assert_eq!(parsed.content, vec!["Water the plants"]);
+
+        let website = &parsed.children[1];
+        assert_eq!(website.heading.as_deref(), Some("+Website"));
+        assert_eq!(website.content_times[0], Duration::from_secs(80 * 60));
+        assert_eq!(website.children[0].heading.as_deref(), Some("@laptop"));
+
+        let files = TodoTxtFormat.serialise(&parsed, &file_path);
+        fs::remove_file(&file_path).unwrap();
+        assert_eq!(files[0].1, TASKS);
+
+        let (reparsed, _) = TodoTxtFormat.parse(&files[0].1, &file_path);
+        assert_eq!(reparsed, parsed);
+        assert!(TodoTxtFormat.verify(&parsed, &files).is_ok());
+    }
+
+    #[test]
+    fn invalid_time_is_reported_and_kept() {
+        let line = "Plan the launch +Website time:99999999999999999999h\n";
+        let file_path = write_temp("invalid-time", line);
+
+        let (parsed, diagnostics) = TodoTxtFormat.parse(line, &file_path);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].target.task, Some(0));
+        assert_eq!(parsed.children[0].content_times[0], Duration::default());
+
+        let files = TodoTxtFormat.serialise(&parsed, &file_path);
+        fs::remove_file(&file_path).unwrap();
+        assert_eq!(files[0].1, line);
+    }
+
+    #[test]
+    fn tracked_time_reads_both_forms() {
+        assert_eq!(TodoLine::tracked_time("4800"), Some(Duration::from_secs(4800)));
+        assert_eq!(TodoLine::tracked_time("1h20m"), Some(Duration::from_secs(4800)));
+        assert_eq!(TodoLine::tracked_time("soon"), None);
+    }
+
+    #[test]
+    fn markdown_does_not_look_like_todotxt() {
+        assert!(looks_like_todotxt(TASKS));
+        assert!(!looks_like_todotxt("# Notes +Website\n- [ ] Call the bank @phone\n"));
+        assert!(!looks_like_todotxt("Plain notes\nwithout any tags\n"));
+    }
+}
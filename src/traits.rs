@@ -1,7 +1,10 @@
 use crossterm::event::KeyCode;
+use std::path::{Path, PathBuf};
 
 use crate::app::SessionState;
+use crate::diagnostic::Diagnostic;
 use crate::info_subtype::InfoSubType;
+use crate::node::Node;
 
 pub trait EventHandler {
     fn handle_events(&mut self, key_code: KeyCode) -> SessionState;
@@ -10,3 +13,84 @@ pub trait EventHandler {
 pub trait ViewEventHandler {
     fn handle_events(&mut self, key_code: KeyCode) -> Result<(InfoSubType, String), String>;
 }
+
+/// A task file backend. `parse` reports anything it could not make sense of alongside the
+/// tree, and `serialise` returns the contents of every file the tree should be written to,
+/// starting with `file_path` itself.
+pub trait TaskFormat {
+    fn parse(&self, buf: &str, file_path: &Path) -> (Node, Vec<Diagnostic>);
+    fn serialise(&self, root_node: &Node, file_path: &Path) -> Vec<(PathBuf, String)>;
+
+    /// Whether the format places tasks under headings from the tasks themselves, so headings
+    /// cannot be added or renamed and tasks cannot be moved by hand.
+    fn fixed_layout(&self) -> bool {
+        false
+    }
+
+    /// Checks that serialised output reads back as `root_node`. On failure returns the file
+    /// that would not round-trip and the first difference found.
+    fn verify(&self, root_node: &Node, files: &[(PathBuf, String)]) -> Result<(), (PathBuf, String)> {
+        // Nothing is written, so there is nothing that could fail to read back
+        let Some((file_path, contents)) = files.first() else {
+            return Ok(());
+        };
+        let (parsed, _) = self.parse(contents, file_path);
+
+        match Node::first_difference(root_node, &parsed) {
+            Some(difference) => Err((file_path.clone(), difference)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown_serialiser::markdown_serialiser::MarkdownFormat;
+
+    /// Writes every task as not done, so completed tasks do not read back.
+    struct LossyFormat;
+
+    impl TaskFormat for LossyFormat {
+        fn parse(&self, buf: &str, _file_path: &Path) -> (Node, Vec<Diagnostic>) {
+            Node::convert_with_diagnostics(buf)
+        }
+
+        fn serialise(&self, root_node: &Node, file_path: &Path) -> Vec<(PathBuf, String)> {
+            let mut contents = String::new();
+            for name in root_node.content.iter() {
+                contents.push_str(&format!("- [ ] {}\n", name));
+            }
+            vec![(file_path.to_path_buf(), contents)]
+        }
+    }
+
+    #[test]
+    fn verify_reports_the_first_difference() {
+        let root_node = Node::convert_from("- [ ] Write the parser\n- [x] Sketch the layout\n");
+        let file_path = Path::new("tasks.md");
+        let files = LossyFormat.serialise(&root_node, file_path);
+
+        let (rejected_path, difference) = LossyFormat.verify(&root_node, &files).unwrap_err();
+        assert_eq!(rejected_path, file_path);
+        assert_eq!(
+            difference,
+            "Task 2 under the root: expected '[x] Sketch the layout', found '[ ] Sketch the layout'"
+        );
+    }
+
+    #[test]
+    fn verify_rejects_task_names_that_would_read_back_as_headings() {
+        let format = MarkdownFormat { follow_links: false };
+        let mut root_node = Node::convert_from("# Project\n- [ ] Write the parser\n");
+        root_node.children[0].content[0] = "Write the parser\n# Injected".to_string();
+
+        let files = format.serialise(&root_node, Path::new("tasks.md"));
+        assert!(format.verify(&root_node, &files).is_err());
+    }
+
+    #[test]
+    fn verify_accepts_nothing_to_write() {
+        assert_eq!(LossyFormat.verify(&Node::new(), &[]), Ok(()));
+    }
+}